@@ -0,0 +1,3820 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+#[cfg(feature = "wasm")]
+use wasm_timer::Instant;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+
+/// Maximum number of generations kept by `Universe::track_population_history`.
+const POPULATION_HISTORY_LIMIT: usize = 10_000;
+
+/// Number of recent state hashes `Universe::tick_checked` compares against, which bounds
+/// the longest oscillator period it detects.
+const STATE_HISTORY_LIMIT: usize = 64;
+
+/// Weight of the latest tick in the moving average behind `Universe::avg_tick_ms`.
+const TICK_AVERAGE_WEIGHT: f64 = 0.1;
+
+/// Each byte value spread out to one byte per bit, bit `i` becoming byte `i` of the word,
+/// for unpacking the Simd implementation's words into cells.
+const SPREAD_BITS: [u64; 256] = {
+    let mut table = [0; 256];
+    let mut value = 0;
+    while value < 256 {
+        let mut bit = 0;
+        while bit < 8 {
+            table[value] |= ((value as u64 >> bit) & 1) << (8 * bit);
+            bit += 1;
+        }
+        value += 1;
+    }
+    table
+};
+
+/// Default ticks per second used by `Universe::update`.
+const DEFAULT_TPS: f64 = 60.0;
+
+/// Default limit of the `--max-memory` startup check, in bytes.
+pub const DEFAULT_MAX_MEMORY: u64 = 512 * 1024 * 1024;
+
+/// Worst-case bytes per cell of the HashSet implementation when every cell is alive: the
+/// live set, the previous and the next generation and the cells to check each hold an
+/// 8-byte key plus a control byte per entry.
+pub const HASHSET_BYTES_PER_LIVE_CELL: u64 = 4 * 9;
+
+/// Sets up the browser side of the library, installing a panic hook that logs the panic
+/// message to the console instead of an opaque `unreachable` trap. Only the first call
+/// has an effect, so every entry point can call it.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn init() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(console_error_panic_hook::set_once);
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = log)]
+    fn console_log(message: &str);
+}
+
+/// Writes a diagnostic line to the browser console, or to stderr outside of the browser.
+fn log_message(message: &str) {
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    console_log(message);
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    eprintln!("{}", message);
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Implementation {
+    Naive,
+    HashSet,
+    Parallel,
+    /// Reversible second-order Life: the next generation is the Life rule applied to the
+    /// current one, XORed with the previous one. Runs backwards with `Universe::tick_back`.
+    SecondOrder,
+    /// Bitsliced: each tick packs the rows into 64-bit words and counts the neighbors of 64
+    /// cells at once with bitwise adders. Falls back to the naive path for custom rule
+    /// functions, isotropic rules, the neighbor table, a sheared torus and grids thinner
+    /// than 3 cells. The fastest implementation on a single thread.
+    Simd,
+}
+
+/// Parses an implementation by the name the CLI uses: `naive`, `hash`, `parallel`,
+/// `second-order` or `simd`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn implementation_from_str(name: &str) -> Option<Implementation> {
+    match name {
+        "naive" => Some(Implementation::Naive),
+        "hash" => Some(Implementation::HashSet),
+        "parallel" => Some(Implementation::Parallel),
+        "second-order" => Some(Implementation::SecondOrder),
+        "simd" => Some(Implementation::Simd),
+        _ => None,
+    }
+}
+
+/// The CLI name of an implementation, the inverse of `implementation_from_str`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn implementation_to_str(implementation: Implementation) -> String {
+    match implementation {
+        Implementation::Naive => "naive",
+        Implementation::HashSet => "hash",
+        Implementation::Parallel => "parallel",
+        Implementation::SecondOrder => "second-order",
+        Implementation::Simd => "simd",
+    }
+    .to_string()
+}
+
+/// How `Universe::new_with_seed_kind` scatters the initial live cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeedKind {
+    /// Every cell is alive with probability `density`.
+    Uniform { density: f64 },
+    /// Blobs around `clusters` random centers on an otherwise empty grid: cells within
+    /// `radius` of a center are alive with probability `density`. Blobs are clipped at the
+    /// edges.
+    Clustered { clusters: u32, radius: u32, density: f64 },
+}
+
+/// Multiply-rotate hasher in the style of rustc's FxHasher. Cell coordinates are small
+/// integers that need no protection against collision attacks, so this is much cheaper than
+/// the default SipHash, and without a random seed the live set iterates in the same order
+/// on every run.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CellHasher {
+    hash: u64,
+}
+
+impl CellHasher {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(CellHasher::SEED);
+    }
+}
+
+impl Hasher for CellHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.add(byte as u64);
+        }
+    }
+
+    fn write_u32(&mut self, word: u32) {
+        self.add(word as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Live cells of the HashSet implementation as `(x, y)` pairs.
+pub type CellSet = HashSet<(u32, u32), BuildHasherDefault<CellHasher>>;
+
+/// Number of live neighbors of a cell. A Moore neighborhood of radius `r` has
+/// `(2r + 1)^2 - 1` cells, so this covers radii up to 127 (65 024 neighbors),
+/// well beyond the 8 neighbors of the radius-1 neighborhood used by `Rule`.
+pub type NeighborCount = u16;
+
+/// What lies beyond the edges of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoundaryMode {
+    /// Edges wrap around, the grid is a torus. With `Universe::set_shear` it is a twisted
+    /// torus instead.
+    #[default]
+    Toroidal,
+    /// Cells outside the grid are permanently dead.
+    Dead,
+}
+
+impl fmt::Display for BoundaryMode {
+    /// Formats the mode by its CLI name, `toroidal` or `dead`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoundaryMode::Toroidal => write!(f, "toroidal"),
+            BoundaryMode::Dead => write!(f, "dead"),
+        }
+    }
+}
+
+impl std::str::FromStr for BoundaryMode {
+    type Err = ParseError;
+
+    fn from_str(name: &str) -> Result<BoundaryMode, ParseError> {
+        match name {
+            "toroidal" => Ok(BoundaryMode::Toroidal),
+            "dead" => Ok(BoundaryMode::Dead),
+            _ => Err(ParseError::new(1, 1, format!("Invalid boundary '{}', expected 'toroidal' or 'dead'", name))),
+        }
+    }
+}
+
+/// Outer-totalistic transition table, indexed by the number of live neighbors (0..=8).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    survival: [bool; 9],
+    birth: [bool; 9],
+}
+
+impl Rule {
+    pub fn new(survival: [bool; 9], birth: [bool; 9]) -> Rule {
+        Rule { survival, birth }
+    }
+
+    /// Conway's Game of Life (B3/S23).
+    pub fn conway() -> Rule {
+        let mut survival = [false; 9];
+        let mut birth = [false; 9];
+        survival[2] = true;
+        survival[3] = true;
+        birth[3] = true;
+        Rule { survival, birth }
+    }
+
+    fn next_state(&self, alive: bool, live_neighbors: NeighborCount) -> bool {
+        if alive {
+            self.survival[live_neighbors as usize]
+        } else {
+            self.birth[live_neighbors as usize]
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    /// Formats the rule in canonical `B.../S...` notation, e.g. `B3/S23`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = |table: &[bool; 9]| -> String {
+            (0..9).filter(|&count| table[count]).map(|count| count.to_string()).collect()
+        };
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+impl std::str::FromStr for Rule {
+    type Err = ParseError;
+
+    /// Parses a `B.../S...` rulestring, case-insensitively and in either order.
+    fn from_str(rulestring: &str) -> Result<Rule, ParseError> {
+        let mut survival = [false; 9];
+        let mut birth = [false; 9];
+        let mut seen = (false, false);
+        let mut column = 1;
+
+        for part in rulestring.split('/') {
+            let mut chars = part.chars();
+            let table = match chars.next().map(|char| char.to_ascii_uppercase()) {
+                Some('B') if !seen.0 => {
+                    seen.0 = true;
+                    &mut birth
+                }
+                Some('S') if !seen.1 => {
+                    seen.1 = true;
+                    &mut survival
+                }
+                _ => return Err(ParseError::new(1, column, "Expected 'B' or 'S' section")),
+            };
+            for (offset, char) in chars.enumerate() {
+                match char.to_digit(10) {
+                    Some(count) if count <= 8 => table[count as usize] = true,
+                    _ => return Err(ParseError::new(1, column + offset + 1, format!("Invalid neighbor count '{}'", char))),
+                }
+            }
+            column += part.chars().count() + 1;
+        }
+
+        if seen != (true, true) {
+            return Err(ParseError::new(1, 1, "Expected both a 'B' and an 'S' section"));
+        }
+        Ok(Rule { survival, birth })
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule::conway()
+    }
+}
+
+/// Letters of the Hensel notation for 0..=4 live neighbors. A count above 4 uses the letters
+/// of `8 - count`, each standing for the complement of that configuration.
+const HENSEL_LETTERS: [&str; 5] = ["", "ce", "ceaikn", "ceaiknjqry", "ceaiknjqrytwz"];
+
+/// One neighbor mask per Hensel letter, in the order of `HENSEL_LETTERS`. Bit `i` is the
+/// `i`-th neighbor in row-major order: NW, N, NE, W, E, SW, S, SE.
+const HENSEL_MASKS: [&[u8]; 5] = [
+    &[0],
+    &[1, 2],
+    &[5, 10, 3, 24, 17, 36],
+    &[37, 26, 11, 7, 50, 13, 14, 38, 25, 49],
+    &[165, 90, 15, 29, 51, 39, 58, 54, 27, 53, 57, 46, 60],
+];
+
+/// Where each neighbor bit goes when the neighborhood is rotated a quarter turn clockwise,
+/// and when it is mirrored left to right.
+const ROTATE_NEIGHBORS: [u8; 8] = [2, 4, 7, 1, 6, 0, 3, 5];
+const MIRROR_NEIGHBORS: [u8; 8] = [2, 1, 0, 4, 3, 7, 6, 5];
+
+fn permute_neighbors(mask: u8, permutation: &[u8; 8]) -> u8 {
+    (0..8).filter(|&bit| mask & (1 << bit) != 0).fold(0, |permuted, bit| permuted | (1 << permutation[bit]))
+}
+
+/// The Hensel configuration for `count` live neighbors and the letter at `letter_index`.
+fn hensel_mask(count: usize, letter_index: usize) -> u8 {
+    if count <= 4 {
+        HENSEL_MASKS[count][letter_index]
+    } else {
+        !HENSEL_MASKS[8 - count][letter_index]
+    }
+}
+
+fn hensel_letters(count: usize) -> &'static str {
+    HENSEL_LETTERS[count.min(8 - count)]
+}
+
+/// Isotropic non-totalistic transition table, indexed by the mask of live neighbors rather
+/// than their count. Every mask of a configuration's rotations and reflections shares its
+/// entry, so the 256 entries only hold the 51 distinct configurations of the Hensel notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IsotropicRule {
+    survival: [bool; 256],
+    birth: [bool; 256],
+}
+
+impl IsotropicRule {
+    fn next_state(&self, alive: bool, neighbor_mask: u8) -> bool {
+        if alive {
+            self.survival[neighbor_mask as usize]
+        } else {
+            self.birth[neighbor_mask as usize]
+        }
+    }
+
+    /// Marks `mask` and all of its rotations and reflections in `table`.
+    fn set_symmetric(table: &mut [bool; 256], mask: u8) {
+        let mut rotated = mask;
+        for _ in 0..4 {
+            table[rotated as usize] = true;
+            table[permute_neighbors(rotated, &MIRROR_NEIGHBORS) as usize] = true;
+            rotated = permute_neighbors(rotated, &ROTATE_NEIGHBORS);
+        }
+    }
+}
+
+impl From<Rule> for IsotropicRule {
+    fn from(rule: Rule) -> IsotropicRule {
+        let mut survival = [false; 256];
+        let mut birth = [false; 256];
+        for mask in 0..256 {
+            let count = (mask as u8).count_ones() as usize;
+            survival[mask] = rule.survival[count];
+            birth[mask] = rule.birth[count];
+        }
+        IsotropicRule { survival, birth }
+    }
+}
+
+impl fmt::Display for IsotropicRule {
+    /// Formats the rule in Hensel notation, e.g. `B2a3/S12-k`. A count is written as a plain
+    /// digit when all of its configurations are present, otherwise with the shorter of the
+    /// present letters or the absent ones after a `-`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let section = |table: &[bool; 256]| -> String {
+            let mut section = String::new();
+            for count in 0..=8 {
+                let letters = hensel_letters(count);
+                let present: String = letters
+                    .chars()
+                    .enumerate()
+                    .filter(|&(index, _)| table[hensel_mask(count, index) as usize])
+                    .map(|(_, letter)| letter)
+                    .collect();
+                let absent: String = letters.chars().filter(|&letter| !present.contains(letter)).collect();
+                if count == 0 || count == 8 {
+                    if table[hensel_mask(count, 0) as usize] {
+                        section.push_str(&count.to_string());
+                    }
+                } else if absent.is_empty() {
+                    section.push_str(&count.to_string());
+                } else if !present.is_empty() && present.len() <= absent.len() {
+                    section.push_str(&format!("{}{}", count, present));
+                } else if !present.is_empty() {
+                    section.push_str(&format!("{}-{}", count, absent));
+                }
+            }
+            section
+        };
+        write!(f, "B{}/S{}", section(&self.birth), section(&self.survival))
+    }
+}
+
+impl std::str::FromStr for IsotropicRule {
+    type Err = ParseError;
+
+    /// Parses a rulestring in Hensel notation such as `B2a/S12`: each neighbor count may be
+    /// followed by the letters of the configurations it is restricted to, or by `-` and the
+    /// letters it excludes. Plain `B.../S...` rulestrings are accepted as well.
+    fn from_str(rulestring: &str) -> Result<IsotropicRule, ParseError> {
+        let mut survival = [false; 256];
+        let mut birth = [false; 256];
+        let mut seen = (false, false);
+        let mut column = 1;
+
+        for part in rulestring.split('/') {
+            let mut chars = part.chars();
+            let table = match chars.next().map(|char| char.to_ascii_uppercase()) {
+                Some('B') if !seen.0 => {
+                    seen.0 = true;
+                    &mut birth
+                }
+                Some('S') if !seen.1 => {
+                    seen.1 = true;
+                    &mut survival
+                }
+                _ => return Err(ParseError::new(1, column, "Expected 'B' or 'S' section")),
+            };
+
+            let chars: Vec<char> = chars.collect();
+            let mut index = 0;
+            while index < chars.len() {
+                let char_column = column + index + 1;
+                let count = match chars[index].to_digit(10) {
+                    Some(count) if count <= 8 => count as usize,
+                    _ => return Err(ParseError::new(1, char_column, format!("Invalid neighbor count '{}'", chars[index]))),
+                };
+                index += 1;
+                let negated = chars.get(index) == Some(&'-');
+                if negated {
+                    index += 1;
+                }
+
+                let letters = hensel_letters(count);
+                let mut selected = vec![false; letters.len().max(1)];
+                let start = index;
+                while let Some(&letter) = chars.get(index).filter(|char| char.is_ascii_alphabetic()) {
+                    match letters.find(letter.to_ascii_lowercase()) {
+                        Some(letter_index) => selected[letter_index] = true,
+                        None => {
+                            return Err(ParseError::new(1, column + index + 1, format!("Invalid configuration '{}' for {} neighbors", letter, count)));
+                        }
+                    }
+                    index += 1;
+                }
+                if negated && index == start {
+                    return Err(ParseError::new(1, column + index + 1, "Expected configuration letters after '-'"));
+                }
+
+                let restricted = index > start;
+                for (letter_index, &selected) in selected.iter().enumerate() {
+                    if !restricted || selected != negated {
+                        IsotropicRule::set_symmetric(table, hensel_mask(count, letter_index));
+                    }
+                }
+            }
+            column += part.chars().count() + 1;
+        }
+
+        if seen != (true, true) {
+            return Err(ParseError::new(1, 1, "Expected both a 'B' and an 'S' section"));
+        }
+        Ok(IsotropicRule { survival, birth })
+    }
+}
+
+/// Characters the plaintext readers treat as live cells by default, `*` is common in
+/// patterns downloaded from LifeWiki.
+pub const ALIVE_CHARS: [char; 2] = ['O', '*'];
+
+/// Names accepted by `Pattern::named`.
+pub const PATTERN_NAMES: [&str; 10] = [
+    "block", "beehive", "loaf", "blinker", "toad", "beacon", "glider", "lwss", "r-pentomino", "glider-gun",
+];
+
+/// Still lifes and oscillators `Pattern::classify` recognizes.
+pub const OBJECT_NAMES: [&str; 6] = ["block", "beehive", "loaf", "blinker", "toad", "beacon"];
+
+/// Shifts `cells` so the smallest row and column are 0 and sorts them, giving equal
+/// shapes equal cell lists wherever they are.
+fn normalize_cells(cells: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let min_row = cells.iter().map(|&(row, _)| row).min().unwrap_or(0);
+    let min_col = cells.iter().map(|&(_, col)| col).min().unwrap_or(0);
+    let mut normalized: Vec<(u32, u32)> = cells.iter().map(|&(row, col)| (row - min_row, col - min_col)).collect();
+    normalized.sort_unstable();
+    normalized
+}
+
+/// Bounds on what the RLE parser accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RleLimits {
+    /// Longest allowed run, e.g. `1000o`.
+    pub max_run_length: u32,
+    /// Most live cells a pattern may decode to, and most cells an RLE file's grid may have.
+    pub max_cells: usize,
+}
+
+impl Default for RleLimits {
+    fn default() -> RleLimits {
+        RleLimits {
+            max_run_length: 1 << 20,
+            max_cells: 1 << 26,
+        }
+    }
+}
+
+/// A pattern as `(row, col)` offsets of its live cells from its top-left corner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    cells: Vec<(u32, u32)>,
+}
+
+impl Pattern {
+    pub fn new(cells: Vec<(u32, u32)>) -> Pattern {
+        Pattern { cells }
+    }
+
+    /// Looks up one of the built-in patterns listed in `PATTERN_NAMES`.
+    pub fn named(name: &str) -> Option<Pattern> {
+        let cells: &[(u32, u32)] = match name {
+            "block" => &[(0, 0), (0, 1), (1, 0), (1, 1)],
+            "beehive" => &[(0, 1), (0, 2), (1, 0), (1, 3), (2, 1), (2, 2)],
+            "loaf" => &[(0, 1), (0, 2), (1, 0), (1, 3), (2, 1), (2, 3), (3, 2)],
+            "blinker" => &[(0, 0), (0, 1), (0, 2)],
+            "toad" => &[(0, 1), (0, 2), (0, 3), (1, 0), (1, 1), (1, 2)],
+            "beacon" => &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 2), (2, 3), (3, 2), (3, 3)],
+            "glider" => &[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)],
+            "lwss" => &[(0, 1), (0, 4), (1, 0), (2, 0), (2, 4), (3, 0), (3, 1), (3, 2), (3, 3)],
+            "r-pentomino" => &[(0, 1), (0, 2), (1, 0), (1, 1), (2, 1)],
+            "glider-gun" => &[
+                (0, 24), (1, 22), (1, 24), (2, 12), (2, 13), (2, 20), (2, 21), (2, 34), (2, 35),
+                (3, 11), (3, 15), (3, 20), (3, 21), (3, 34), (3, 35), (4, 0), (4, 1), (4, 10),
+                (4, 16), (4, 20), (4, 21), (5, 0), (5, 1), (5, 10), (5, 14), (5, 16), (5, 17),
+                (5, 22), (5, 24), (6, 10), (6, 16), (6, 24), (7, 11), (7, 15), (8, 12), (8, 13),
+            ],
+            _ => return None,
+        };
+        Some(Pattern::new(cells.to_vec()))
+    }
+
+    /// Parses a pattern in run length encoded (RLE) format with the default `RleLimits`.
+    /// The `x = .., y = ..` header and `#` comment lines are skipped, the cells are read
+    /// up to the closing `!`.
+    pub fn from_rle(text: &str) -> Result<Pattern, ParseError> {
+        Pattern::from_rle_with_limits(text, RleLimits::default())
+    }
+
+    /// Parses RLE, rejecting runs and patterns larger than `limits` instead of trying
+    /// to allocate them, which matters for untrusted input.
+    pub fn from_rle_with_limits(text: &str, limits: RleLimits) -> Result<Pattern, ParseError> {
+        let mut cells = Vec::new();
+        let (mut row, mut col) = (0u32, 0u32);
+        let mut count: Option<u32> = None;
+
+        for (line_index, line) in text.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('#') || trimmed.starts_with('x') {
+                continue;
+            }
+            for (column, char) in line.char_indices() {
+                let error = |message: String| ParseError::new(line_index + 1, column + 1, message);
+                let too_large = || error("Pattern exceeds the maximum size".to_string());
+                let run = count.unwrap_or(1);
+                match char {
+                    '0'..='9' => {
+                        let digit = char.to_digit(10).unwrap();
+                        let run = count
+                            .unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|count| count.checked_add(digit))
+                            .filter(|&run| run <= limits.max_run_length)
+                            .ok_or_else(|| error(format!("Run count exceeds the maximum of {}", limits.max_run_length)))?;
+                        count = Some(run);
+                        continue;
+                    }
+                    'b' | '.' => col = col.checked_add(run).ok_or_else(too_large)?,
+                    '$' => {
+                        row = row.checked_add(run).ok_or_else(too_large)?;
+                        col = 0;
+                    }
+                    '!' => return Ok(Pattern::new(cells)),
+                    char if char.is_whitespace() => continue,
+                    char if char.is_ascii_alphabetic() => {
+                        let end = col.checked_add(run).ok_or_else(too_large)?;
+                        if cells.len() + run as usize > limits.max_cells {
+                            return Err(error(format!("More than the maximum of {} live cells", limits.max_cells)));
+                        }
+                        cells.extend((col..end).map(|col| (row, col)));
+                        col = end;
+                    }
+                    char => return Err(error(format!("Unexpected character '{}'", char))),
+                }
+                count = None;
+            }
+        }
+
+        Ok(Pattern::new(cells))
+    }
+
+    /// Parses a plaintext (`.cells`) pattern: `!` lines are comments, `O` or `*` is alive
+    /// and `.` is dead. Any other character is an error.
+    pub fn from_plaintext(text: &str) -> Result<Pattern, ParseError> {
+        Pattern::from_plaintext_with(text, &ALIVE_CHARS, true)
+    }
+
+    /// Like `from_plaintext`, with `alive` as the live-cell characters. Unless `strict`,
+    /// characters other than those and `.` are dead cells instead of an error.
+    pub fn from_plaintext_with(text: &str, alive: &[char], strict: bool) -> Result<Pattern, ParseError> {
+        let mut cells = Vec::new();
+        let rows = text.lines().enumerate().filter(|(_, line)| !line.starts_with('!'));
+        for (row, (line_index, line)) in rows.enumerate() {
+            for (col, char) in line.chars().enumerate() {
+                match char {
+                    char if alive.contains(&char) => cells.push((row as u32, col as u32)),
+                    '.' => {}
+                    char if char.is_whitespace() || !strict => {}
+                    char => {
+                        return Err(ParseError::new(line_index + 1, col + 1, format!("Unexpected character '{}'", char)))
+                    }
+                }
+            }
+        }
+        Ok(Pattern::new(cells))
+    }
+
+    /// Parses a block of `.` and `O` (or `*`) lines written in code, such as
+    /// `".O.\n..O\nOOO"` for a glider, like `Universe::assert_eq_pattern` reads them: each
+    /// line is trimmed, so the block may be indented, and blank lines are skipped. Panics
+    /// on any other character, as the pattern is a literal rather than input.
+    #[track_caller]
+    pub fn from_ascii(ascii: &str) -> Pattern {
+        let rows = ascii.lines().map(str::trim).filter(|line| !line.is_empty());
+        let mut cells = Vec::new();
+        for (row, line) in rows.enumerate() {
+            for (col, char) in line.chars().enumerate() {
+                match char {
+                    char if ALIVE_CHARS.contains(&char) => cells.push((row as u32, col as u32)),
+                    '.' => {}
+                    char => panic!("Unexpected character '{}' in pattern line {}", char, row + 1),
+                }
+            }
+        }
+        Pattern::new(cells)
+    }
+
+    /// Parses a Life 1.06 pattern, one `x y` pair per live cell. Coordinates may be
+    /// negative, the pattern is shifted so its top-left corner is at the origin.
+    pub fn from_life106(text: &str) -> Result<Pattern, ParseError> {
+        let mut coordinates = Vec::new();
+        for (line_index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let numbers = line
+                .split_whitespace()
+                .map(|number| number.parse::<i64>())
+                .collect::<Result<Vec<i64>, _>>()
+                .map_err(|_| ParseError::new(line_index + 1, 1, "Failed to parse coordinates"))?;
+            if numbers.len() != 2 {
+                return Err(ParseError::new(line_index + 1, 1, "Expected 'x y' coordinates"));
+            }
+            coordinates.push((numbers[1], numbers[0]));
+        }
+
+        let min_row = coordinates.iter().map(|&(row, _)| row).min().unwrap_or(0);
+        let min_col = coordinates.iter().map(|&(_, col)| col).min().unwrap_or(0);
+        let cells = coordinates
+            .into_iter()
+            .map(|(row, col)| ((row - min_row) as u32, (col - min_col) as u32))
+            .collect();
+        Ok(Pattern::new(cells))
+    }
+
+    /// Parses Golly's two-state macrocell (`.mc`) format, a quadtree in which each line
+    /// defines a node: an 8x8 leaf drawn with `.`, `*` and `$`, or `<level> <nw> <ne> <sw>
+    /// <se>` referring to earlier lines by number, 0 being an empty node. The last node is
+    /// the root. `#` lines, such as `#R` with the rule, are skipped. The pattern is
+    /// shifted so its top-left corner is at the origin.
+    pub fn from_macrocell(text: &str) -> Result<Pattern, ParseError> {
+        Pattern::from_macrocell_with_limits(text, RleLimits::default())
+    }
+
+    /// Like `from_macrocell`, rejecting patterns of more than `limits.max_cells` live
+    /// cells, since a small file can describe a vast population.
+    pub fn from_macrocell_with_limits(text: &str, limits: RleLimits) -> Result<Pattern, ParseError> {
+        enum Node {
+            Leaf(Vec<(u64, u64)>),
+            Inner { level: u32, children: [usize; 4] },
+        }
+
+        // Node n is nodes[n - 1], 0 stands for an empty node of any level
+        let mut nodes: Vec<(usize, Node)> = Vec::new();
+        for (line_index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let error = |message: String| ParseError::new(line_index + 1, 1, message);
+            if line_index == 0 {
+                if !line.starts_with("[M2]") {
+                    return Err(error("Expected a '[M2]' header".to_string()));
+                }
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let node = if line.starts_with(['.', '*', '$']) {
+                let mut cells = Vec::new();
+                let (mut row, mut col) = (0, 0);
+                for (column, char) in line.char_indices() {
+                    match char {
+                        '$' => (row, col) = (row + 1, 0),
+                        '.' | '*' if row < 8 && col < 8 => {
+                            if char == '*' {
+                                cells.push((row, col));
+                            }
+                            col += 1;
+                        }
+                        '.' | '*' => return Err(ParseError::new(line_index + 1, column + 1, "Leaf exceeds 8x8 cells")),
+                        char => return Err(ParseError::new(line_index + 1, column + 1, format!("Unexpected character '{}'", char))),
+                    }
+                }
+                Node::Leaf(cells)
+            } else {
+                let numbers = line
+                    .split_whitespace()
+                    .map(|number| number.parse::<usize>())
+                    .collect::<Result<Vec<usize>, _>>()
+                    .map_err(|_| error("Failed to parse node".to_string()))?;
+                let [level, nw, ne, sw, se] = numbers[..] else {
+                    return Err(error("Expected '<level> <nw> <ne> <sw> <se>'".to_string()));
+                };
+                if !(4..=63).contains(&level) {
+                    return Err(error(format!("Unsupported node level {}, only two-state files with 8x8 leaves can be read", level)));
+                }
+                for child in [nw, ne, sw, se] {
+                    let child_level = match nodes.get(child.wrapping_sub(1)) {
+                        _ if child == 0 => continue,
+                        Some((_, Node::Leaf(_))) => 3,
+                        Some((_, Node::Inner { level, .. })) => *level as usize,
+                        None => return Err(error(format!("Node {} is not defined yet", child))),
+                    };
+                    if child_level != level - 1 {
+                        return Err(error(format!("Node {} has level {}, expected {}", child, child_level, level - 1)));
+                    }
+                }
+                Node::Inner { level: level as u32, children: [nw, ne, sw, se] }
+            };
+            nodes.push((line_index + 1, node));
+        }
+
+        // Shared nodes let a few lines stand for a huge population, count it before decoding
+        let mut populations: Vec<u64> = Vec::with_capacity(nodes.len());
+        for (_, node) in &nodes {
+            let population = match node {
+                Node::Leaf(cells) => cells.len() as u64,
+                Node::Inner { children, .. } => children
+                    .iter()
+                    .map(|&child| if child == 0 { 0 } else { populations[child - 1] })
+                    .fold(0, u64::saturating_add),
+            };
+            populations.push(population);
+        }
+        if let (Some(&(line, _)), Some(&population)) = (nodes.last(), populations.last()) {
+            if population > limits.max_cells as u64 {
+                return Err(ParseError::new(line, 1, format!("More than the maximum of {} live cells", limits.max_cells)));
+            }
+        }
+
+        let mut coordinates = Vec::new();
+        let mut pending = if nodes.is_empty() { Vec::new() } else { vec![(nodes.len(), 0u64, 0u64)] };
+        while let Some((index, row, col)) = pending.pop() {
+            match &nodes[index - 1].1 {
+                Node::Leaf(cells) => {
+                    coordinates.extend(cells.iter().map(|&(leaf_row, leaf_col)| (row + leaf_row, col + leaf_col)));
+                }
+                Node::Inner { level, children } => {
+                    let half = 1u64 << (level - 1);
+                    let offsets = [(0, 0), (0, half), (half, 0), (half, half)];
+                    for (&child, (delta_row, delta_col)) in children.iter().zip(offsets) {
+                        if child != 0 {
+                            pending.push((child, row + delta_row, col + delta_col));
+                        }
+                    }
+                }
+            }
+        }
+
+        let min_row = coordinates.iter().map(|&(row, _)| row).min().unwrap_or(0);
+        let min_col = coordinates.iter().map(|&(_, col)| col).min().unwrap_or(0);
+        let cells = coordinates
+            .into_iter()
+            .map(|(row, col)| Ok((u32::try_from(row - min_row)?, u32::try_from(col - min_col)?)))
+            .collect::<Result<Vec<(u32, u32)>, std::num::TryFromIntError>>()
+            .map_err(|_| ParseError::new(1, 1, "Pattern exceeds the maximum size"))?;
+        Ok(Pattern::new(cells))
+    }
+
+    /// Parses RLE, plaintext or Life 1.06, detecting the format from the text itself.
+    pub fn from_text(text: &str) -> Result<Pattern, ParseError> {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with("#Life 1.06") {
+            Pattern::from_life106(text)
+        } else if trimmed.starts_with('#') || trimmed.starts_with("x =") || trimmed.starts_with("x=") {
+            Pattern::from_rle(text)
+        } else if trimmed.starts_with('!') || trimmed.chars().all(|char| matches!(char, '.' | 'O' | '*' | '\n' | '\r')) {
+            Pattern::from_plaintext(text)
+        } else {
+            Err(ParseError::new(1, 1, "Unrecognized pattern format"))
+        }
+    }
+
+    /// Names the pattern if it is one of the objects in `OBJECT_NAMES`, in any phase,
+    /// rotation or reflection, and `None` otherwise. The pattern must be the object alone.
+    pub fn classify(&self) -> Option<&'static str> {
+        let cells = normalize_cells(&self.cells);
+        OBJECT_NAMES.into_iter().find(|name| {
+            let object = Pattern::named(name).expect("object names are built-in patterns");
+            // Oscillators of period 2 are matched in both phases
+            let mut universe = Universe::empty(object.width() + 4, object.height() + 4, Implementation::Naive);
+            universe.set_boundary_mode(BoundaryMode::Dead);
+            universe.insert_pattern(&object, 2, 2);
+            (0..2).any(|_| {
+                let phase = universe.live_cells();
+                universe.tick();
+                let size = phase.iter().map(|&(row, col)| row.max(col)).max().unwrap_or(0);
+                let orientations: [&dyn Fn(u32, u32) -> (u32, u32); 8] = [
+                    &|row, col| (row, col),
+                    &|row, col| (col, size - row),
+                    &|row, col| (size - row, size - col),
+                    &|row, col| (size - col, row),
+                    &|row, col| (row, size - col),
+                    &|row, col| (size - row, col),
+                    &|row, col| (col, row),
+                    &|row, col| (size - col, size - row),
+                ];
+                orientations.iter().any(|orient| {
+                    let oriented: Vec<(u32, u32)> = phase.iter().map(|&(row, col)| orient(row, col)).collect();
+                    normalize_cells(&oriented) == cells
+                })
+            })
+        })
+    }
+
+    pub fn cells(&self) -> &[(u32, u32)] {
+        &self.cells
+    }
+
+    pub fn width(&self) -> u32 {
+        self.cells.iter().map(|&(_, col)| col + 1).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.cells.iter().map(|&(row, _)| row + 1).max().unwrap_or(0)
+    }
+}
+
+/// Error raised while loading a seed file or pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Malformed content, pointing at the offending 1-based line and column.
+    Syntax { line: usize, column: usize, message: String },
+    /// The file could not be read, e.g. because it does not exist.
+    Io { path: String, kind: io::ErrorKind, message: String },
+}
+
+impl ParseError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError::Syntax { line, column, message: message.into() }
+    }
+
+    fn io(path: &str, error: io::Error) -> ParseError {
+        ParseError::Io { path: path.to_string(), kind: error.kind(), message: error.to_string() }
+    }
+
+    /// The description of the problem, without its location.
+    pub fn message(&self) -> &str {
+        match self {
+            ParseError::Syntax { message, .. } | ParseError::Io { message, .. } => message,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax { line, column, message } => write!(f, "line {}, column {}: {}", line, column, message),
+            ParseError::Io { path, message, .. } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Error raised when an operation needs two universes of equal dimensions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    pub expected: (u32, u32),
+    pub found: (u32, u32),
+}
+
+impl fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected a {}x{} universe, found {}x{}",
+            self.expected.0, self.expected.1, self.found.0, self.found.1
+        )
+    }
+}
+
+impl std::error::Error for DimensionMismatch {}
+
+/// Error raised when a caller-provided buffer does not have the length an operation needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferSizeMismatch {
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl fmt::Display for BufferSizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected a buffer of {} bytes, found {}", self.expected, self.found)
+    }
+}
+
+impl std::error::Error for BufferSizeMismatch {}
+
+/// Snapshot passed to the on-tick callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickStats {
+    pub generation: u64,
+    pub population: u32,
+}
+
+impl TickStats {
+    /// Formats the stats and the tick's duration as `gen=123 pop=456 dt=0.8ms`, the line
+    /// `Universe::set_verbose` logs.
+    pub fn log_line(&self, elapsed_ms: f64) -> String {
+        format!("gen={} pop={} dt={:.1}ms", self.generation, self.population, elapsed_ms)
+    }
+}
+
+/// Work counted while profiling is enabled, see `Universe::enable_profiling`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProfilingCounters {
+    /// Buffers allocated by ticks, e.g. the next generation and scratch rows.
+    pub allocations: u64,
+    /// Buffers reused from an earlier tick instead of being allocated.
+    pub allocations_avoided: u64,
+    /// Cells whose next state was computed.
+    pub cells_visited: u64,
+    /// Cell reads made to count neighbors. Counting a row at a time reads each cell of the
+    /// three rows once, 3 reads per cell, while counting per cell reads all 8 neighbors.
+    pub neighbor_lookups: u64,
+}
+
+/// How the cells changed in the last tick, see `Universe::track_transitions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TransitionCounts {
+    pub births: u32,
+    pub survivals: u32,
+    /// Live cells that died with fewer neighbors than the smallest count they survive with.
+    pub underpopulation_deaths: u32,
+    /// All other deaths: too many neighbors, or a count in a gap of the rule's survival set.
+    pub overpopulation_deaths: u32,
+}
+
+/// Outcome of `Universe::run_until_stable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stability {
+    /// The state no longer changes, this includes a universe that died out.
+    StillLife,
+    /// The state repeats every `period` generations.
+    Oscillator { period: u64 },
+    /// The pattern's shape repeats every `period` generations, moved by `dx` columns and `dy` rows.
+    Spaceship { period: u64, dx: i64, dy: i64 },
+    /// No repetition was found within the generation limit.
+    Unsettled,
+}
+
+/// Outcome of `Universe::analyze_methuselah`. Generations are values of the universe's
+/// generation counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MethuselahReport {
+    pub peak_population: u32,
+    /// First generation that reached the peak population.
+    pub peak_generation: u64,
+    /// First generation of the final still life, oscillation or spaceship, `None` if the
+    /// pattern did not settle within the generation limit.
+    pub settled_at: Option<u64>,
+    pub stability: Stability,
+    pub final_population: u32,
+}
+
+/// Cells that changed in a tick, from `Universe::tick_events`, as row-major flat indices
+/// in increasing order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TickEvents {
+    pub births: Vec<u32>,
+    pub deaths: Vec<u32>,
+}
+
+/// Memory currently held by a universe's cells, from `Universe::memory_estimate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// Bytes allocated for the grid, the previous generation and the live sets, counting
+    /// the sets by capacity at `HASHSET_BYTES_PER_LIVE_CELL / 4` bytes per entry.
+    pub bytes: u64,
+    pub live_cells: u32,
+    /// Entries the live set has room for. Far above `live_cells` after a population died
+    /// back, which `Universe::shrink_to_fit` reclaims.
+    pub hashset_capacity: usize,
+}
+
+/// Per-tick durations measured by `Universe::profile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProfileReport {
+    pub count: u32,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub median: Duration,
+}
+
+/// Reflection symmetries of the live pattern within its bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symmetry {
+    /// Unchanged when flipped top to bottom.
+    pub horizontal: bool,
+    /// Unchanged when flipped left to right.
+    pub vertical: bool,
+    /// Unchanged when reflected across either diagonal of a square bounding box.
+    pub diagonal: bool,
+}
+
+/// First line of a seed file: `width height iterations`, optionally followed by the rule
+/// and the boundary mode, which default to Conway and toroidal.
+pub struct SeedHeader {
+    pub width: u32,
+    pub height: u32,
+    pub iterations: u32,
+    rule: (Rule, Option<IsotropicRule>, u8),
+    boundary: BoundaryMode,
+}
+
+/// Custom per-cell rule, mapping a cell's state and live neighbor count to its next state.
+pub type RuleFn = dyn Fn(bool, NeighborCount) -> bool + Send + Sync;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Universe {
+    width: u32,
+    height: u32,
+    cells: Vec<bool>,
+    /// Keyed by `(x, y)` = `(col, row)`, unlike the `(row, col)` order of the public API.
+    cells_hashset: CellSet,
+    implementation: Implementation,
+    rule: Rule,
+    isotropic_rule: Option<IsotropicRule>,
+    generation: u64,
+    neighbor_table: Option<Vec<[usize; 8]>>,
+    population_history: Option<VecDeque<u32>>,
+    recent_states: VecDeque<(u64, u64)>,
+    running: bool,
+    tps: f64,
+    accumulated_ms: f64,
+    avg_tick_ms: Option<f64>,
+    on_tick: Option<Box<dyn FnMut(TickStats) + Send + Sync>>,
+    verbose: bool,
+    boundary: BoundaryMode,
+    /// Rows a cell moves south when wrapping around the east edge of the torus.
+    shear: i32,
+    last_change_generation: u64,
+    pattern_name: Option<String>,
+    author: Option<String>,
+    comment: Option<String>,
+    dedup_neighbors: bool,
+    states: u8,
+    decay: Vec<u8>,
+    rule_fn: Option<Box<RuleFn>>,
+    profiling: Option<ProfilingCounters>,
+    previous_population: Option<u32>,
+    transitions: Option<TransitionCounts>,
+    previous_cells: Vec<bool>,
+    previous_cells_hashset: Option<CellSet>,
+    /// Live cells as constructed, `(row, col)`, restored by `reset_to_seed`.
+    seed: Vec<(u32, u32)>,
+}
+
+impl Universe {
+    pub fn new(width: u32, height: u32, implementation: Implementation, seed_path: Option<&String>) -> Universe {
+        match seed_path {
+            Some(path) => Universe::from_seed_file(width, height, implementation, path, false)
+                .unwrap_or_else(|error| panic!("Failed to parse seed file: {}", error)),
+            None => {
+                let cells = Universe::initialize_randomly(width, height, &mut rand::thread_rng());
+                Universe::from_cells(width, height, implementation, cells)
+            }
+        }
+    }
+
+    /// Creates a randomly initialized universe with the default implementation, Simd,
+    /// which is the fastest on a single thread and so also in the browser.
+    pub fn new_default(width: u32, height: u32) -> Universe {
+        Universe::new(width, height, Implementation::Simd, None)
+    }
+
+    /// Creates a randomly initialized universe whose cells only depend on `seed`,
+    /// so runs can be reproduced.
+    pub fn new_with_seed(width: u32, height: u32, implementation: Implementation, seed: u64) -> Universe {
+        let cells = Universe::initialize_randomly(width, height, &mut StdRng::seed_from_u64(seed));
+        Universe::from_cells(width, height, implementation, cells)
+    }
+
+    /// Creates a universe whose initial cells are drawn as described by `kind`, only
+    /// depending on `seed`. Densities are clamped to the range 0 to 1.
+    pub fn new_with_seed_kind(width: u32, height: u32, implementation: Implementation, kind: SeedKind, seed: u64) -> Universe {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let cells = match kind {
+            SeedKind::Uniform { density } => {
+                (0..width * height).map(|_| rng.gen_bool(density.clamp(0.0, 1.0))).collect()
+            }
+            SeedKind::Clustered { clusters, radius, density } => {
+                let mut cells = vec![false; (width * height) as usize];
+                if width > 0 && height > 0 {
+                    for _ in 0..clusters {
+                        let (center_row, center_col) = (rng.gen_range(0..height), rng.gen_range(0..width));
+                        for row in center_row.saturating_sub(radius)..=(center_row + radius).min(height - 1) {
+                            for col in center_col.saturating_sub(radius)..=(center_col + radius).min(width - 1) {
+                                let (delta_row, delta_col) = (row.abs_diff(center_row) as u64, col.abs_diff(center_col) as u64);
+                                if delta_row * delta_row + delta_col * delta_col <= radius as u64 * radius as u64
+                                    && rng.gen_bool(density.clamp(0.0, 1.0))
+                                {
+                                    cells[(row * width + col) as usize] = true;
+                                }
+                            }
+                        }
+                    }
+                }
+                cells
+            }
+        };
+        Universe::from_cells(width, height, implementation, cells)
+    }
+
+    /// Loads a seed file, returning an error instead of panicking on malformed input.
+    /// In `strict` mode, characters other than `O`, `*` and `.`, rows longer than the width
+    /// and rows beyond the height are rejected instead of being ignored. The rule and the
+    /// boundary mode are taken from the header when present, and the generation counter
+    /// resumes at the header's iteration count.
+    pub fn from_seed_file(width: u32, height: u32, implementation: Implementation, path: &str, strict: bool) -> Result<Universe, ParseError> {
+        let content = fs::read_to_string(path).map_err(|error| ParseError::io(path, error))?;
+        Universe::from_seed_str(width, height, implementation, &content, strict)
+    }
+
+    /// Like `from_seed_file`, with the seed's content already read, e.g. from stdin.
+    pub fn from_seed_str(width: u32, height: u32, implementation: Implementation, content: &str, strict: bool) -> Result<Universe, ParseError> {
+        let header = Universe::parse_seed_header(content)?;
+        if header.width != width || header.height != height {
+            return Err(ParseError::new(1, 1, "Provided width and height do not match the file's dimensions."));
+        }
+        let cells = Universe::initialize_from_seed(width, height, content, &ALIVE_CHARS, strict)?;
+        let mut universe = Universe::from_cells(width, height, implementation, cells);
+        let (rule, isotropic_rule, states) = header.rule;
+        (universe.rule, universe.isotropic_rule) = (rule, isotropic_rule);
+        universe.set_states(states);
+        universe.boundary = header.boundary;
+        universe.set_generation(header.iterations as u64);
+        Ok(universe)
+    }
+
+    /// Loads a seed file sized by its own `width height iterations` header, so the caller
+    /// does not need to know the dimensions in advance.
+    pub fn from_file(path: &str, implementation: Implementation) -> Result<Universe, ParseError> {
+        let content = fs::read_to_string(path).map_err(|error| ParseError::io(path, error))?;
+        let header = Universe::parse_seed_header(&content)?;
+        Universe::from_seed_str(header.width, header.height, implementation, &content, false)
+    }
+
+    /// Loads a plain PBM (P1) image as the seed, black pixels become live cells.
+    /// The universe takes the dimensions of the image.
+    pub fn from_pbm(path: &str, implementation: Implementation) -> Result<Universe, ParseError> {
+        let (width, height, cells) = Universe::initialize_from_pbm(path)?;
+        Ok(Universe::from_cells(width, height, implementation, cells))
+    }
+
+    /// Loads an RLE file, sized by its `x = .., y = ..` header. The rule from the header and
+    /// the `#N`, `#O` and `#C` metadata lines are kept, and written back on RLE export.
+    pub fn from_rle(path: &str, implementation: Implementation) -> Result<Universe, ParseError> {
+        let content = fs::read_to_string(path).map_err(|error| ParseError::io(path, error))?;
+        let mut dimensions = None;
+        let mut rules = (Rule::default(), None, 2);
+        let (mut pattern_name, mut author, mut comments) = (None, None, Vec::new());
+
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if let Some(metadata) = line.strip_prefix('#') {
+                let value = metadata.get(1..).unwrap_or("").trim().to_string();
+                match metadata.chars().next() {
+                    Some('N') => pattern_name = Some(value),
+                    Some('O') => author = Some(value),
+                    Some('C') | Some('c') => comments.push(value),
+                    _ => {}
+                }
+            } else if line.starts_with('x') {
+                let mut width = None;
+                let mut height = None;
+                for field in line.split(',') {
+                    let (key, value) = field.split_once('=').unwrap_or((field, ""));
+                    let value = value.trim();
+                    let error = || ParseError::new(line_index + 1, 1, format!("Invalid header field '{}'", field.trim()));
+                    match key.trim() {
+                        "x" => width = Some(value.parse::<u32>().map_err(|_| error())?),
+                        "y" => height = Some(value.parse::<u32>().map_err(|_| error())?),
+                        "rule" => rules = Universe::parse_rule(value).map_err(|_| error())?,
+                        _ => {}
+                    }
+                }
+                match (width, height) {
+                    (Some(width), Some(height)) => dimensions = Some((width, height)),
+                    _ => return Err(ParseError::new(line_index + 1, 1, "Header is missing 'x' or 'y'")),
+                }
+                break;
+            }
+        }
+
+        let (width, height) = dimensions.ok_or_else(|| ParseError::new(1, 1, "Missing 'x = .., y = ..' header"))?;
+        let limits = RleLimits::default();
+        if width as u64 * height as u64 > limits.max_cells as u64 {
+            return Err(ParseError::new(1, 1, format!("Grid exceeds the maximum of {} cells", limits.max_cells)));
+        }
+        let mut universe = Universe::empty(width, height, implementation);
+        universe.insert_pattern(&Pattern::from_rle_with_limits(&content, limits)?, 0, 0);
+        (universe.rule, universe.isotropic_rule) = (rules.0, rules.1);
+        universe.set_states(rules.2);
+        universe.pattern_name = pattern_name;
+        universe.author = author;
+        universe.comment = if comments.is_empty() { None } else { Some(comments.join("\n")) };
+        universe.remember_seed();
+        Ok(universe)
+    }
+
+    /// Loads a Golly macrocell (`.mc`) file, sized to the pattern's bounding box, see
+    /// `Pattern::from_macrocell`. The rule (`#R`) and generation (`#G`) are kept.
+    pub fn from_macrocell(path: &str, implementation: Implementation) -> Result<Universe, ParseError> {
+        let content = fs::read_to_string(path).map_err(|error| ParseError::io(path, error))?;
+        let limits = RleLimits::default();
+        let pattern = Pattern::from_macrocell_with_limits(&content, limits)?;
+        if pattern.width() as u64 * pattern.height() as u64 > limits.max_cells as u64 {
+            return Err(ParseError::new(1, 1, format!("Grid exceeds the maximum of {} cells", limits.max_cells)));
+        }
+        let mut universe = Universe::empty(pattern.width(), pattern.height(), implementation);
+        universe.insert_pattern(&pattern, 0, 0);
+        universe.remember_seed();
+        for (line_index, line) in content.lines().enumerate() {
+            let error = |message: &str| ParseError::new(line_index + 1, 1, message);
+            if let Some(rule) = line.strip_prefix("#R") {
+                universe.set_rule_string(rule.trim()).map_err(|_| error("Invalid rule"))?;
+            } else if let Some(generation) = line.strip_prefix("#G") {
+                universe.set_generation(generation.trim().parse::<u64>().map_err(|_| error("Invalid generation"))?);
+            }
+        }
+        Ok(universe)
+    }
+
+    /// Loads a file made of several small grids, each preceded by an `@pattern <row> <col>`
+    /// directive, and ORs them into one universe at the given offsets. The first line holds
+    /// the universe's `width height iterations`, like a regular seed file.
+    pub fn from_multi(path: &str, implementation: Implementation) -> Result<Universe, ParseError> {
+        let (width, height, cells) = Universe::initialize_from_multi(path)?;
+        Ok(Universe::from_cells(width, height, implementation, cells))
+    }
+
+    /// Estimates the bytes a universe of the given size needs while ticking. The array
+    /// implementations hold the grid and the previous generation, one byte per cell each.
+    /// The HashSet implementation keeps the initial grid, plus sets that grow with the live
+    /// population, which this lower bound does not cover. The Simd implementation adds
+    /// three packed copies of the grid, one bit per cell each.
+    pub fn estimated_memory(width: u32, height: u32, implementation: &Implementation) -> u64 {
+        let cells = width as u64 * height as u64;
+        match implementation {
+            Implementation::HashSet => cells,
+            Implementation::Naive | Implementation::Parallel | Implementation::SecondOrder => 2 * cells,
+            Implementation::Simd => 2 * cells + 3 * cells.div_ceil(8),
+        }
+    }
+
+    /// Creates a universe with all cells dead. A width or height of 0 is allowed and yields
+    /// a universe without cells, which stays empty and whose ticks change nothing.
+    pub fn empty(width: u32, height: u32, implementation: Implementation) -> Universe {
+        let cells = vec![false; (width * height) as usize];
+        Universe::from_cells(width, height, implementation, cells)
+    }
+
+    /// Builds the universe around the given cells. The hashset representation is only
+    /// populated for the HashSet implementation, array-only runs never pay for it.
+    fn from_cells(width: u32, height: u32, implementation: Implementation, cells: Vec<bool>) -> Universe {
+        let cells_hashset = match implementation {
+            Implementation::HashSet => Universe::create_hashset(&cells, width, height),
+            _ => CellSet::default(),
+        };
+
+        let mut universe = Universe {
+            width,
+            height,
+            cells,
+            cells_hashset,
+            implementation,
+            rule: Rule::default(),
+            isotropic_rule: None,
+            generation: 0,
+            neighbor_table: None,
+            population_history: None,
+            recent_states: VecDeque::new(),
+            running: true,
+            tps: DEFAULT_TPS,
+            accumulated_ms: 0.0,
+            avg_tick_ms: None,
+            on_tick: None,
+            verbose: false,
+            boundary: BoundaryMode::default(),
+            shear: 0,
+            last_change_generation: 0,
+            pattern_name: None,
+            author: None,
+            comment: None,
+            dedup_neighbors: false,
+            states: 2,
+            decay: Vec::new(),
+            rule_fn: None,
+            profiling: None,
+            previous_population: None,
+            transitions: None,
+            previous_cells: Vec::new(),
+            previous_cells_hashset: None,
+            seed: Vec::new(),
+        };
+        universe.remember_seed();
+        universe.debug_check_invariants();
+        universe
+    }
+
+    /// Records the current live cells as the state `reset_to_seed` goes back to.
+    /// Constructors that place their cells after `from_cells` call it again.
+    fn remember_seed(&mut self) {
+        self.seed = self.live_cells();
+    }
+
+    /// Panics in debug builds when a buffer no longer matches the dimensions, catching a
+    /// grid and its `width`/`height` drifting apart where it happens rather than as an
+    /// out-of-bounds index later. Called after every operation that replaces or resizes
+    /// the buffers, and after editing cells.
+    fn debug_check_invariants(&self) {
+        let size = self.width as usize * self.height as usize;
+        debug_assert_eq!(self.cells.len(), size, "cells do not match {}x{}", self.width, self.height);
+        debug_assert!(self.decay.is_empty() || self.decay.len() == size, "decay states do not match the grid");
+        debug_assert!(
+            self.neighbor_table.as_ref().is_none_or(|table| table.len() == size),
+            "neighbor table does not match the grid"
+        );
+    }
+
+    /// Name from the `#N` line of an imported RLE file.
+    pub fn pattern_name(&self) -> Option<&str> {
+        self.pattern_name.as_deref()
+    }
+
+    /// Author from the `#O` line of an imported RLE file.
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// Comments from the `#C` (or `#c`) lines of an imported RLE file, one per line.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Overrides the rule with an arbitrary function of a cell's state and live neighbor
+    /// count, called for every visited cell on each tick. This is slower than a transition
+    /// table but allows any experiment. The same caveat applies to the HashSet
+    /// implementation: dead cells without live neighbors are never visited.
+    pub fn set_rule_fn(&mut self, rule_fn: impl Fn(bool, NeighborCount) -> bool + Send + Sync + 'static) {
+        self.rule_fn = Some(Box::new(rule_fn));
+    }
+
+    /// Removes the custom rule function, falling back to the configured rule.
+    pub fn clear_rule_fn(&mut self) {
+        self.rule_fn = None;
+    }
+
+    fn next_cell_state(&self, alive: bool, live_neighbors: NeighborCount) -> bool {
+        match &self.rule_fn {
+            Some(rule_fn) => rule_fn(alive, live_neighbors),
+            None => self.rule.next_state(alive, live_neighbors),
+        }
+    }
+
+    /// The current rule in canonical `B.../S...` notation, or in Hensel notation for an
+    /// isotropic non-totalistic rule. Generations rules end in `/C<states>`.
+    pub fn rule_string(&self) -> String {
+        let rule = match &self.isotropic_rule {
+            Some(rule) => rule.to_string(),
+            None => self.rule.to_string(),
+        };
+        if self.states > 2 {
+            format!("{}/C{}", rule, self.states)
+        } else {
+            rule
+        }
+    }
+
+    /// Replaces the rule with one parsed from a `B.../S...` rulestring. Isotropic
+    /// non-totalistic rulestrings in Hensel notation, such as `B2a/S12`, are accepted too,
+    /// as are Generations rules with a number of states, either as `B2/S/C3` or `/2/3`.
+    pub fn set_rule_string(&mut self, rulestring: &str) -> Result<(), ParseError> {
+        let (rule, isotropic_rule, states) = Universe::parse_rule(rulestring)?;
+        (self.rule, self.isotropic_rule) = (rule, isotropic_rule);
+        self.set_states(states);
+        Ok(())
+    }
+
+    /// Number of cell states: 2 for regular rules, more for a Generations rule, where a
+    /// live cell that does not survive passes through `states - 2` dying states before
+    /// it is fully dead. Dying cells can not be born and do not count as live neighbors.
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// Sets the number of cell states, see `states`. Values below 2 are treated as 2.
+    /// Any dying cells are cleared.
+    pub fn set_states(&mut self, states: u8) {
+        self.states = states.max(2);
+        self.decay = if self.states > 2 { vec![0; self.cells.len()] } else { Vec::new() };
+        self.debug_check_invariants();
+    }
+
+    /// State of the cell at `(row, col)` for coloring: 0 when dead, 1 when alive, and
+    /// 2 up to `states() - 1` while dying under a Generations rule. 0 outside of the grid.
+    pub fn cell_state(&self, row: u32, col: u32) -> u8 {
+        if !self.in_bounds(row, col) {
+            return 0;
+        }
+        if self.get_cell(row, col) {
+            return 1;
+        }
+        self.decay.get((row * self.width + col) as usize).copied().unwrap_or(0)
+    }
+
+    /// Replaces the rule with an isotropic non-totalistic one, deciding each cell's next
+    /// state from which of its neighbors are alive rather than how many. Neighbor counting
+    /// then happens per cell, so this is slower than an outer-totalistic rule.
+    pub fn set_isotropic_rule(&mut self, rule: IsotropicRule) {
+        self.isotropic_rule = Some(rule);
+    }
+
+    /// Parses an outer-totalistic rulestring, falling back to Hensel notation, along with
+    /// the number of states. A third section makes it a Generations rule, written either
+    /// `B.../S.../C<states>` or, without letters, `<survival>/<birth>/<states>`.
+    fn parse_rule(rulestring: &str) -> Result<(Rule, Option<IsotropicRule>, u8), ParseError> {
+        if let [first, second, states] = rulestring.split('/').collect::<Vec<_>>()[..] {
+            let column = first.chars().count() + second.chars().count() + 3;
+            let states = states
+                .strip_prefix(['C', 'c'])
+                .unwrap_or(states)
+                .parse::<u8>()
+                .ok()
+                .filter(|&states| states >= 2)
+                .ok_or_else(|| ParseError::new(1, column, "Expected a number of states from 2 to 255"))?;
+            let rulestring = if first.starts_with(|char: char| char.is_ascii_alphabetic()) {
+                format!("{}/{}", first, second)
+            } else {
+                format!("B{}/S{}", second, first)
+            };
+            let (rule, isotropic_rule, _) = Universe::parse_rule(&rulestring)?;
+            return Ok((rule, isotropic_rule, states));
+        }
+
+        match rulestring.parse::<Rule>() {
+            Ok(rule) => Ok((rule, None, 2)),
+            Err(_) => Ok((Rule::default(), Some(rulestring.parse()?), 2)),
+        }
+    }
+
+    /// On grids thinner than 3 cells, the wrapped Moore neighborhood visits some cells
+    /// several times (on a 2x2 torus, the cell to the left is also the cell to the right).
+    /// When enabled, every distinct neighbor is counted once instead. Larger grids are
+    /// unaffected.
+    pub fn set_dedup_neighbors(&mut self, enabled: bool) {
+        self.dedup_neighbors = enabled;
+    }
+
+    pub fn set_boundary_mode(&mut self, boundary: BoundaryMode) {
+        self.boundary = boundary;
+    }
+
+    pub fn boundary_mode(&self) -> BoundaryMode {
+        self.boundary
+    }
+
+    /// Twists the torus: wrapping around the east edge moves `shear` rows south, and around
+    /// the west edge as many rows north, while the north and south edges wrap straight.
+    /// Only applies to the toroidal boundary; 0, the default, is the plain torus. The fast
+    /// row-at-a-time paths only handle the plain torus, a sheared one is stepped per cell.
+    pub fn set_shear(&mut self, shear: i32) {
+        self.shear = shear;
+    }
+
+    pub fn shear(&self) -> i32 {
+        self.shear
+    }
+
+    /// Replaces the rule with an arbitrary outer-totalistic transition table.
+    /// `alive_survives[n]` decides whether a live cell with `n` live neighbors survives,
+    /// `dead_births[n]` whether a dead cell with `n` live neighbors is born.
+    /// Note that the HashSet implementation only visits live cells and their neighbors,
+    /// so rules with birth on 0 neighbors are not supported there.
+    pub fn set_transition_table(&mut self, alive_survives: [bool; 9], dead_births: [bool; 9]) {
+        self.rule = Rule::new(alive_survives, dead_births);
+        self.isotropic_rule = None;
+    }
+
+    fn initialize_randomly(width: u32, height: u32, rng: &mut impl Rng) -> Vec<bool> {
+        (0..width * height)
+            .map(|_| rng.gen::<bool>())
+            .collect()
+    }
+
+    /// Reads the `width height iterations [rule [boundary]]` header on the first line of a
+    /// seed file.
+    pub fn parse_seed_header(content: &str) -> Result<SeedHeader, ParseError> {
+        let first_line = content.lines().next().ok_or_else(|| ParseError::new(1, 1, "Missing header line"))?;
+        let fields: Vec<&str> = first_line.split_whitespace().collect();
+        if !(3..=5).contains(&fields.len()) {
+            return Err(ParseError::new(1, 1, "Expected 'width height iterations [rule [boundary]]' on the first line"));
+        }
+        let numbers = fields[..3]
+            .iter()
+            .map(|number| number.parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| ParseError::new(1, 1, "Failed to parse dimensions"))?;
+        let rule = match fields.get(3) {
+            Some(rulestring) => Universe::parse_rule(rulestring)
+                .map_err(|error| ParseError::new(1, 1, format!("Invalid rule '{}': {}", rulestring, error.message())))?,
+            None => (Rule::default(), None, 2),
+        };
+        let boundary = fields.get(4).map(|boundary| boundary.parse()).transpose()?.unwrap_or_default();
+
+        Ok(SeedHeader { width: numbers[0], height: numbers[1], iterations: numbers[2], rule, boundary })
+    }
+
+    fn initialize_from_seed(width: u32, height: u32, content: &str, alive: &[char], strict: bool) -> Result<Vec<bool>, ParseError> {
+        let mut cells = vec![false; (width * height) as usize];
+
+        for (y, line) in content.lines().skip(1).enumerate() {
+            // The header occupies the first line
+            let line_number = y + 2;
+            if strict && y >= height as usize && !line.is_empty() {
+                return Err(ParseError::new(line_number, 1, format!("More rows than the declared height of {}", height)));
+            }
+            for (x, char) in line.chars().enumerate() {
+                if strict {
+                    if x >= width as usize {
+                        return Err(ParseError::new(line_number, x + 1, format!("Row is longer than the declared width of {}", width)));
+                    }
+                    if !alive.contains(&char) && char != '.' {
+                        return Err(ParseError::new(line_number, x + 1, format!("Unexpected character '{}'", char)));
+                    }
+                }
+                if x < width as usize && y < height as usize {
+                    cells[y * width as usize + x] = alive.contains(&char);
+                }
+            }
+        }
+
+        Ok(cells)
+    }
+
+    fn initialize_from_pbm(path: &str) -> Result<(u32, u32, Vec<bool>), ParseError> {
+        let content = fs::read_to_string(path).map_err(|error| ParseError::io(path, error))?;
+
+        // Split into whitespace separated words, remembering where each one starts
+        let mut words: Vec<(usize, usize, &str)> = Vec::new();
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("");
+            let mut start = None;
+            for (column, char) in line.char_indices().chain(std::iter::once((line.len(), ' '))) {
+                match (start, char.is_whitespace()) {
+                    (None, false) => start = Some(column),
+                    (Some(word_start), true) => {
+                        words.push((line_index + 1, word_start + 1, &line[word_start..column]));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut words = words.into_iter();
+        match words.next() {
+            Some((_, _, "P1")) => {}
+            Some((line, column, _)) => return Err(ParseError::new(line, column, "Only plain PBM (P1) images are supported")),
+            None => return Err(ParseError::new(1, 1, "Empty PBM file")),
+        }
+        let mut dimension = |name: &str| match words.next() {
+            Some((line, column, word)) => word
+                .parse::<u32>()
+                .map_err(|_| ParseError::new(line, column, format!("Failed to parse {}", name))),
+            None => Err(ParseError::new(1, 1, format!("Missing {}", name))),
+        };
+        let width = dimension("width")?;
+        let height = dimension("height")?;
+
+        // Pixels may or may not be separated by whitespace
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        for (line, column, word) in words {
+            for (offset, char) in word.char_indices() {
+                if cells.len() == (width * height) as usize {
+                    return Err(ParseError::new(line, column + offset, "More pixels than the declared dimensions"));
+                }
+                match char {
+                    '0' => cells.push(false),
+                    '1' => cells.push(true),
+                    _ => return Err(ParseError::new(line, column + offset, format!("Unexpected character '{}'", char))),
+                }
+            }
+        }
+        if cells.len() != (width * height) as usize {
+            return Err(ParseError::new(content.lines().count(), 1, "Fewer pixels than the declared dimensions"));
+        }
+
+        Ok((width, height, cells))
+    }
+
+    fn initialize_from_multi(path: &str) -> Result<(u32, u32, Vec<bool>), ParseError> {
+        let content = fs::read_to_string(path).map_err(|error| ParseError::io(path, error))?;
+        let mut lines = content.lines().enumerate();
+
+        let first_line = lines.next().map(|(_, line)| line).unwrap_or("");
+        let dimensions = first_line
+            .split_whitespace()
+            .map(|number| number.parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| ParseError::new(1, 1, "Failed to parse dimensions"))?;
+        if dimensions.len() != 3 {
+            return Err(ParseError::new(1, 1, "Expected width, height and iterations"));
+        }
+        let (width, height) = (dimensions[0], dimensions[1]);
+
+        let mut cells = vec![false; (width * height) as usize];
+        // Offset of the block being read and the row within it
+        let mut block: Option<(u32, u32, u32)> = None;
+
+        for (line_index, line) in lines {
+            let line_number = line_index + 1;
+            if let Some(directive) = line.strip_prefix("@pattern") {
+                let offsets = directive
+                    .split_whitespace()
+                    .map(|number| number.parse::<u32>())
+                    .collect::<Result<Vec<u32>, _>>()
+                    .map_err(|_| ParseError::new(line_number, 1, "Failed to parse pattern offset"))?;
+                if offsets.len() != 2 {
+                    return Err(ParseError::new(line_number, 1, "Expected '@pattern <row> <col>'"));
+                }
+                block = Some((offsets[0], offsets[1], 0));
+                continue;
+            }
+
+            let (row, col, block_row) = match block.as_mut() {
+                Some(block) => block,
+                None if line.trim().is_empty() => continue,
+                None => return Err(ParseError::new(line_number, 1, "Cells outside of an '@pattern' block")),
+            };
+            for (x, char) in line.chars().enumerate() {
+                let cell_row = *row + *block_row;
+                let cell_col = *col + x as u32;
+                if ALIVE_CHARS.contains(&char) && cell_row < height && cell_col < width {
+                    cells[(cell_row * width + cell_col) as usize] = true;
+                }
+            }
+            *block_row += 1;
+        }
+
+        Ok((width, height, cells))
+    }
+
+    /// Re-randomizes the grid in place, keeping the dimensions, implementation and rule,
+    /// and resets the generation counter.
+    pub fn reset(&mut self) {
+        let mut rng = rand::thread_rng();
+        for cell in self.cells.iter_mut() {
+            *cell = rng.gen::<bool>();
+        }
+        self.restart();
+    }
+
+    /// Restores the live cells the universe was constructed with and resets the generation
+    /// counter, to rerun an experiment from the same start. Cells edited after construction
+    /// are not part of the seed, and after a `resize` the seed cells that no longer fit are
+    /// left out. The dimensions, implementation and rule are kept.
+    pub fn reset_to_seed(&mut self) {
+        self.cells.fill(false);
+        for &(row, col) in &self.seed {
+            if row < self.height && col < self.width {
+                self.cells[(row * self.width + col) as usize] = true;
+            }
+        }
+        self.restart();
+    }
+
+    /// Starts over from the cells just written to `cells` at generation 0, forgetting
+    /// everything about earlier generations: the previous generation behind
+    /// `changed_cells` and `cell_fade`, the population behind `population_delta`, the
+    /// states `tick_checked` compares against and the decay of dying cells.
+    fn restart(&mut self) {
+        if let Implementation::HashSet = self.implementation {
+            self.cells_hashset = Universe::create_hashset(&self.cells, self.width, self.height);
+        }
+        self.decay.fill(0);
+        self.previous_cells.clear();
+        self.previous_cells_hashset = None;
+        self.previous_population = None;
+        self.recent_states.clear();
+        self.generation = 0;
+        self.last_change_generation = 0;
+        self.debug_check_invariants();
+    }
+
+    /// Changes the grid to `width` x `height`, keeping the cells that still fit at their
+    /// coordinates: rows and columns beyond the new size are dropped, new ones start dead.
+    /// The generation counter and all settings are kept, the neighbor table is rebuilt if
+    /// one was precomputed.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let mut cells = vec![false; width as usize * height as usize];
+        let mut decay = vec![0; if self.decay.is_empty() { 0 } else { cells.len() }];
+        for row in 0..height.min(self.height) {
+            for col in 0..width.min(self.width) {
+                let (old, new) = ((row * self.width + col) as usize, (row * width + col) as usize);
+                cells[new] = self.get_cell(row, col);
+                if let Some(&state) = self.decay.get(old) {
+                    decay[new] = state;
+                }
+            }
+        }
+
+        if let Implementation::HashSet = self.implementation {
+            self.cells_hashset = Universe::create_hashset(&cells, width, height);
+        }
+        (self.width, self.height, self.cells, self.decay) = (width, height, cells, decay);
+        self.previous_cells = Vec::new();
+        self.previous_cells_hashset = None;
+        let table = self.neighbor_table.is_some();
+        self.neighbor_table = None;
+        self.precompute_neighbors(table);
+        self.debug_check_invariants();
+    }
+
+    /// Same as `resize`, for callers that think of it as setting `width` and `height`.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.resize(width, height);
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn implementation(&self) -> &Implementation {
+        &self.implementation
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Sets the generation counter, e.g. to resume a saved run at its absolute generation.
+    /// The current state counts as the last change, see `last_change_generation`.
+    pub fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+        self.last_change_generation = generation;
+    }
+
+    /// The last generation whose tick changed at least one cell. Stops advancing once
+    /// the universe becomes a still life, so renderers can skip redundant redraws.
+    pub fn last_change_generation(&self) -> u64 {
+        self.last_change_generation
+    }
+
+    /// Precomputes the 8 wrapped neighbor indices of every cell, so the array implementations
+    /// count neighbors with a table lookup instead of modulo arithmetic. Trades
+    /// `width * height * 8` indices of memory for speed on small, repeatedly ticked grids.
+    /// The table only applies to the toroidal boundary, and is not built for grids thinner
+    /// than 3 cells, where some of the 8 neighbors coincide.
+    pub fn precompute_neighbors(&mut self, enabled: bool) {
+        self.neighbor_table = if enabled && self.width >= 3 && self.height >= 3 {
+            Some(Universe::build_neighbor_table(self.width, self.height))
+        } else {
+            None
+        };
+        self.debug_check_invariants();
+    }
+
+    fn build_neighbor_table(width: u32, height: u32) -> Vec<[usize; 8]> {
+        let mut table = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let mut neighbors = [0; 8];
+                let mut i = 0;
+                for delta_y in [height.wrapping_sub(1), 0, 1] {
+                    for delta_x in [width.wrapping_sub(1), 0, 1] {
+                        if delta_y == 0 && delta_x == 0 {
+                            continue;
+                        }
+                        let neighbor_x = (x + delta_x) % width;
+                        let neighbor_y = (y + delta_y) % height;
+                        neighbors[i] = (neighbor_y * width + neighbor_x) as usize;
+                        i += 1;
+                    }
+                }
+                table.push(neighbors);
+            }
+        }
+        table
+    }
+
+    pub fn population(&self) -> u32 {
+        match self.implementation {
+            Implementation::HashSet => self.cells_hashset.len() as u32,
+            _ => self.cells.iter().filter(|&&cell| cell).count() as u32,
+        }
+    }
+
+    /// Signed change in population caused by the last tick, 0 before the first tick.
+    pub fn population_delta(&self) -> i64 {
+        match self.previous_population {
+            Some(previous) => self.population() as i64 - previous as i64,
+            None => 0,
+        }
+    }
+
+    /// Length of the packed representation of `cells_as_bytes`, one bit per cell.
+    pub fn packed_len(&self) -> usize {
+        (self.width as usize * self.height as usize).div_ceil(8)
+    }
+
+    /// The cells in row-major order without copying, `width * height` long. `None` for the
+    /// HashSet implementation, whose ticks only update the hashset, use `live_cells` there.
+    pub fn as_cells(&self) -> Option<&[bool]> {
+        match self.implementation {
+            Implementation::HashSet => None,
+            _ => Some(&self.cells),
+        }
+    }
+
+    /// Same as `as_cells`, next to `live_set` for callers that resolve the implementation
+    /// once and then read cells in a loop without `get_cell`'s per-call match.
+    pub fn cells_slice(&self) -> Option<&[bool]> {
+        self.as_cells()
+    }
+
+    /// The live cells of the HashSet implementation without copying, keyed by `(col, row)`
+    /// unlike the rest of the API. `None` for the array implementations, use `cells_slice`.
+    pub fn live_set(&self) -> Option<&CellSet> {
+        match self.implementation {
+            Implementation::HashSet => Some(&self.cells_hashset),
+            _ => None,
+        }
+    }
+
+    /// Consumes the universe and returns its cells in row-major order, materializing them
+    /// from the hashset for the HashSet implementation.
+    pub fn into_cells(self) -> Vec<bool> {
+        match self.implementation {
+            Implementation::HashSet => self.live_mask(),
+            _ => self.cells,
+        }
+    }
+
+    /// The cells packed one bit per cell in row-major order, cell `i` being bit `i % 8`
+    /// (least significant first) of byte `i / 8`. Unused bits of the last byte are 0.
+    pub fn cells_as_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0; self.packed_len()];
+        self.pack_cells(&mut bytes);
+        bytes
+    }
+
+    /// Ticks once and writes the new generation packed like `cells_as_bytes` into `out`,
+    /// which must be exactly `packed_len` bytes long. Lets a render loop reuse one buffer
+    /// instead of allocating a frame per tick. Nothing is ticked if the length is wrong.
+    pub fn step_and_render(&mut self, out: &mut [u8]) -> Result<(), BufferSizeMismatch> {
+        if out.len() != self.packed_len() {
+            return Err(BufferSizeMismatch { expected: self.packed_len(), found: out.len() });
+        }
+        self.tick();
+        self.pack_cells(out);
+        Ok(())
+    }
+
+    /// Replaces every cell with the bits of `bytes`, packed like `cells_as_bytes`, which
+    /// must be exactly `packed_len` bytes long. Unused bits of the last byte are ignored.
+    /// Nothing changes if the length is wrong.
+    pub fn set_cells_from_bytes(&mut self, bytes: &[u8]) -> Result<(), BufferSizeMismatch> {
+        if bytes.len() != self.packed_len() {
+            return Err(BufferSizeMismatch { expected: self.packed_len(), found: bytes.len() });
+        }
+        self.debug_check_invariants();
+        for (index, cell) in self.cells.iter_mut().enumerate() {
+            *cell = (bytes[index / 8] >> (index % 8)) & 1 == 1;
+        }
+        if let Implementation::HashSet = self.implementation {
+            self.cells_hashset = Universe::create_hashset(&self.cells, self.width, self.height);
+        }
+        self.decay.fill(0);
+        Ok(())
+    }
+
+    fn pack_cells(&self, out: &mut [u8]) {
+        match self.implementation {
+            Implementation::HashSet => {
+                out.fill(0);
+                for &(x, y) in &self.cells_hashset {
+                    let index = y as usize * self.width as usize + x as usize;
+                    out[index / 8] |= 1 << (index % 8);
+                }
+            }
+            _ => {
+                for (byte, chunk) in out.iter_mut().zip(self.cells.chunks(8)) {
+                    *byte = chunk.iter().enumerate().fold(0, |byte, (bit, &cell)| byte | ((cell as u8) << bit));
+                }
+            }
+        }
+    }
+
+    /// Whether no cell is alive.
+    pub fn is_empty(&self) -> bool {
+        match self.implementation {
+            Implementation::HashSet => self.cells_hashset.is_empty(),
+            _ => !self.cells.iter().any(|&cell| cell),
+        }
+    }
+
+    /// Whether the universe is empty and will stay so, letting loops stop early. Rules with
+    /// birth on 0 neighbors, custom rule functions and Generations cells that are still
+    /// dying can all change an empty universe, so they never qualify.
+    fn is_dead_forever(&self) -> bool {
+        let births_on_zero = match &self.isotropic_rule {
+            Some(rule) => rule.birth[0],
+            None => self.rule.birth[0],
+        };
+        let previous_empty = match self.implementation {
+            Implementation::SecondOrder => !self.previous_cells.contains(&true),
+            _ => true,
+        };
+        !births_on_zero
+            && self.rule_fn.is_none()
+            && self.decay.iter().all(|&state| state == 0)
+            && self.is_empty()
+            && previous_empty
+    }
+
+    /// Starts counting the work done by ticks, from zero, or stops and discards the
+    /// counters. Meant for comparing implementations, it costs little but is off by default.
+    pub fn enable_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled.then(ProfilingCounters::default);
+    }
+
+    /// The counters since profiling was enabled, all zero while it is disabled.
+    pub fn profiling_counters(&self) -> ProfilingCounters {
+        self.profiling.unwrap_or_default()
+    }
+
+    fn record_profile(&mut self, allocations: u64, allocations_avoided: u64, cells_visited: u64, neighbor_lookups: u64) {
+        if let Some(counters) = &mut self.profiling {
+            counters.allocations += allocations;
+            counters.allocations_avoided += allocations_avoided;
+            counters.cells_visited += cells_visited;
+            counters.neighbor_lookups += neighbor_lookups;
+        }
+    }
+
+    /// Records a tick of the per-cell array path: one buffer for the next generation, which
+    /// is not allocated if `reused`, plus one per cell when counting distinct neighbors.
+    fn record_per_cell_profile(&mut self, reused: bool) {
+        let cells = self.cells.len() as u64;
+        let distinct = self.boundary == BoundaryMode::Toroidal
+            && self.dedup_neighbors
+            && (self.width < 3 || self.height < 3)
+            && !self.uses_neighbor_masks();
+        self.record_profile(!reused as u64 + if distinct { cells } else { 0 }, reused as u64, cells, 8 * cells);
+    }
+
+    /// Takes the previous generation's buffer to compute the next generation into, so the
+    /// array implementations swap two buffers instead of allocating one per tick. Returns
+    /// whether it could be reused as is; it is only allocated before the first tick or
+    /// when a SecondOrder tick still needs the previous generation. The contents are stale,
+    /// the caller overwrites every cell.
+    fn take_back_buffer(&mut self) -> (Vec<bool>, bool) {
+        let mut buffer = std::mem::take(&mut self.previous_cells);
+        let reused = buffer.len() == self.cells.len();
+        buffer.resize(self.cells.len(), false);
+        (buffer, reused)
+    }
+
+    /// Records the population after every tick, keeping the most recent
+    /// `POPULATION_HISTORY_LIMIT` generations. Disabling it discards the history.
+    pub fn track_population_history(&mut self, enabled: bool) {
+        self.population_history = if enabled { Some(VecDeque::new()) } else { None };
+    }
+
+    /// Counts births, survivals and deaths by cause on every tick, for checking that a rule
+    /// behaves as intended. It costs an extra pass over the grid per tick, so it is off by
+    /// default. Disabling it discards the counts.
+    pub fn track_transitions(&mut self, enabled: bool) {
+        self.transitions = enabled.then(TransitionCounts::default);
+    }
+
+    /// The transitions of the last tick, all zero before the first tick or while
+    /// `track_transitions` is disabled.
+    pub fn last_transition_counts(&self) -> TransitionCounts {
+        self.transitions.unwrap_or_default()
+    }
+
+    /// Smallest neighbor count a live cell can survive with under the active rule, 9 if
+    /// it can not survive at all.
+    fn smallest_survival_count(&self) -> NeighborCount {
+        (0..=8)
+            .find(|&count| match self.isotropic_rule.as_ref().filter(|_| self.uses_neighbor_masks()) {
+                Some(rule) => (0..=255u8).any(|mask| mask.count_ones() == count as u32 && rule.survival[mask as usize]),
+                None => self.next_cell_state(true, count),
+            })
+            .unwrap_or(9)
+    }
+
+    fn count_transitions(&self, previous: &[bool], previous_counts: &[u8]) -> TransitionCounts {
+        let smallest_survival_count = self.smallest_survival_count();
+        let mut transitions = TransitionCounts::default();
+        for (index, (&was_alive, &count)) in previous.iter().zip(previous_counts).enumerate() {
+            let alive = self.get_cell(index as u32 / self.width, index as u32 % self.width);
+            match (was_alive, alive) {
+                (false, true) => transitions.births += 1,
+                (true, true) => transitions.survivals += 1,
+                (true, false) if (count as NeighborCount) < smallest_survival_count => transitions.underpopulation_deaths += 1,
+                (true, false) => transitions.overpopulation_deaths += 1,
+                (false, false) => {}
+            }
+        }
+        transitions
+    }
+
+    pub fn population_history(&self) -> Vec<u32> {
+        match &self.population_history {
+            Some(history) => history.iter().copied().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Number of live neighbors of the cell at `(row, col)`, honoring the boundary mode.
+    /// 0 outside of the grid, which also covers every cell of a zero-sized universe.
+    pub fn neighbor_count(&self, row: u32, col: u32) -> NeighborCount {
+        if !self.in_bounds(row, col) {
+            return 0;
+        }
+        match self.implementation {
+            Implementation::HashSet => self.live_neighbor_count_hashset(col, row),
+            _ => self.live_neighbor_count_array(col, row),
+        }
+    }
+
+    /// Number of live neighbors of the cell at `(row, col)` with the edges wrapping around,
+    /// whatever the boundary mode. 0 outside of the grid.
+    pub fn neighbor_count_toroidal(&self, row: u32, col: u32) -> NeighborCount {
+        if !self.in_bounds(row, col) {
+            return 0;
+        }
+        self.live_neighbor_count_wrapped(col, row, |x, y| self.get_cell(y, x))
+    }
+
+    /// Number of live neighbors of the cell at `(row, col)` with cells beyond the edges
+    /// counting as dead, whatever the boundary mode. 0 outside of the grid.
+    pub fn neighbor_count_bounded(&self, row: u32, col: u32) -> NeighborCount {
+        if !self.in_bounds(row, col) {
+            return 0;
+        }
+        self.live_neighbor_count_bounded(col, row, |x, y| self.get_cell(y, x))
+    }
+
+    /// Live neighbor counts (0..=8) of every cell in row-major order.
+    pub fn neighbor_counts(&self) -> Vec<u8> {
+        let uses_array = !matches!(self.implementation, Implementation::HashSet);
+        if !uses_array || !self.uses_row_counts() {
+            return (0..self.height)
+                .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+                .map(|(row, col)| self.neighbor_count(row, col) as u8)
+                .collect();
+        }
+
+        let width = self.width as usize;
+        let mut counts = vec![0; self.cells.len()];
+        let mut sums = vec![0; width];
+        for (y, row_counts) in counts.chunks_mut(width).enumerate() {
+            self.row_neighbor_counts(y, &mut sums, row_counts);
+        }
+        counts
+    }
+
+    /// Whether `(row, col)` lies on the grid. Editing methods ignore coordinates outside of
+    /// it instead of wrapping them, wrapping only applies to neighbor counting.
+    pub fn in_bounds(&self, row: u32, col: u32) -> bool {
+        row < self.height && col < self.width
+    }
+
+    /// Whether the cell at `(row, col)` is alive, `false` outside of the grid.
+    pub fn get_cell(&self, row: u32, col: u32) -> bool {
+        if !self.in_bounds(row, col) {
+            return false;
+        }
+        match self.implementation {
+            Implementation::HashSet => self.cells_hashset.contains(&(col, row)),
+            _ => self.cells[(row * self.width + col) as usize],
+        }
+    }
+
+    /// The cells of `row` from left to right, empty if the row is outside of the grid.
+    pub fn get_row(&self, row: u32) -> Vec<bool> {
+        if row >= self.height {
+            return Vec::new();
+        }
+        match self.implementation {
+            Implementation::HashSet => (0..self.width).map(|col| self.cells_hashset.contains(&(col, row))).collect(),
+            _ => self.cells[(row * self.width) as usize..((row + 1) * self.width) as usize].to_vec(),
+        }
+    }
+
+    /// The cells of `col` from top to bottom, empty if the column is outside of the grid.
+    pub fn get_column(&self, col: u32) -> Vec<bool> {
+        if col >= self.width {
+            return Vec::new();
+        }
+        match self.implementation {
+            Implementation::HashSet => (0..self.height).map(|row| self.cells_hashset.contains(&(col, row))).collect(),
+            _ => self.cells.iter().skip(col as usize).step_by(self.width as usize).copied().collect(),
+        }
+    }
+
+    /// Sets a single cell, keeping the hashset in sync for the HashSet implementation.
+    /// Does nothing outside of the grid.
+    pub fn set_cell(&mut self, row: u32, col: u32, alive: bool) {
+        if !self.in_bounds(row, col) {
+            return;
+        }
+        self.debug_check_invariants();
+        self.cells[(row * self.width + col) as usize] = alive;
+        if let Some(state) = self.decay.get_mut((row * self.width + col) as usize) {
+            *state = 0;
+        }
+        if let Implementation::HashSet = self.implementation {
+            if alive {
+                self.cells_hashset.insert((col, row));
+            } else {
+                self.cells_hashset.remove(&(col, row));
+            }
+        }
+    }
+
+    /// Flips the cell at `(row, col)`, doing nothing outside of the grid.
+    pub fn toggle_cell(&mut self, row: u32, col: u32) {
+        let alive = self.get_cell(row, col);
+        self.set_cell(row, col, !alive);
+    }
+
+    /// Flips every cell on the segment from `(r0, c0)` to `(r1, c1)`, both ends included,
+    /// as traced by Bresenham's algorithm. Each cell is flipped once, cells outside of the
+    /// grid are skipped.
+    pub fn toggle_line(&mut self, r0: u32, c0: u32, r1: u32, c1: u32) {
+        let (mut row, mut col) = (r0 as i64, c0 as i64);
+        let (end_row, end_col) = (r1 as i64, c1 as i64);
+        let delta_col = (end_col - col).abs();
+        let delta_row = -(end_row - row).abs();
+        let step_col = if col < end_col { 1 } else { -1 };
+        let step_row = if row < end_row { 1 } else { -1 };
+        let mut error = delta_col + delta_row;
+
+        loop {
+            self.toggle_cell(row as u32, col as u32);
+            if (row, col) == (end_row, end_col) {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= delta_row {
+                error += delta_row;
+                col += step_col;
+            }
+            if doubled <= delta_col {
+                error += delta_col;
+                row += step_row;
+            }
+        }
+    }
+
+    /// Sets a batch of cells given by their row-major flat indices, skipping out-of-range ones.
+    pub fn set_cells_from_indices(&mut self, indices: &[u32], alive: bool) {
+        for &index in indices {
+            if index < self.width * self.height {
+                self.set_cell(index / self.width, index % self.width, alive);
+            }
+        }
+    }
+
+    /// Flips `count` distinct randomly chosen cells. The choice only depends on `seed`,
+    /// so injecting the same noise into identical universes gives identical results.
+    pub fn inject_noise(&mut self, count: u32, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let size = (self.width * self.height) as usize;
+        for index in rand::seq::index::sample(&mut rng, size, (count as usize).min(size)) {
+            self.toggle_cell(index as u32 / self.width, index as u32 % self.width);
+        }
+    }
+
+    /// Re-seeds the `height` by `width` rectangle with its top-left corner at `(row, col)`,
+    /// each cell alive with probability `density` (clamped to 0 to 1), leaving the rest of
+    /// the grid intact. The rectangle is clipped to the grid, and the result only depends
+    /// on `seed`.
+    pub fn randomize_region(&mut self, row: u32, col: u32, height: u32, width: u32, density: f64, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let density = density.clamp(0.0, 1.0);
+        for cell_row in row..row.saturating_add(height).min(self.height) {
+            for cell_col in col..col.saturating_add(width).min(self.width) {
+                self.set_cell(cell_row, cell_col, rng.gen_bool(density));
+            }
+        }
+    }
+
+    /// Brings the pattern's cells to life with its top-left corner at `(row, col)`.
+    /// Cells falling outside the grid are clipped.
+    pub fn insert_pattern(&mut self, pattern: &Pattern, row: u32, col: u32) {
+        for &(delta_row, delta_col) in pattern.cells() {
+            self.set_cell(row.saturating_add(delta_row), col.saturating_add(delta_col), true);
+        }
+    }
+
+    /// ORs the live cells of `other` into this universe, shifted by the given offsets.
+    /// Cells landing beyond the edges wrap around on a toroidal universe and are clipped
+    /// with a dead boundary. The dimensions of the two universes need not match.
+    pub fn merge(&mut self, other: &Universe, row_offset: u32, col_offset: u32) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        for (row, col) in other.live_cells() {
+            let row = row as u64 + row_offset as u64;
+            let col = col as u64 + col_offset as u64;
+            match self.boundary {
+                BoundaryMode::Toroidal => {
+                    let (col, row) = self.wrap(col as i64, row as i64);
+                    self.set_cell(row, col, true);
+                }
+                BoundaryMode::Dead => {
+                    if row < self.height as u64 && col < self.width as u64 {
+                        self.set_cell(row as u32, col as u32, true);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses pasted text as RLE, plaintext or Life 1.06, whichever it looks like, and
+    /// inserts it with its top-left corner at `(row, col)`.
+    pub fn insert_auto(&mut self, text: &str, row: u32, col: u32) -> Result<(), ParseError> {
+        let pattern = Pattern::from_text(text)?;
+        self.insert_pattern(&pattern, row, col);
+        Ok(())
+    }
+
+    /// Inserts one of the built-in patterns, returning `false` if there is none with that name.
+    pub fn insert_named_pattern(&mut self, name: &str, row: u32, col: u32) -> bool {
+        match Pattern::named(name) {
+            Some(pattern) => {
+                self.insert_pattern(&pattern, row, col);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn create_hashset(cells: &[bool], width: u32, height: u32) -> CellSet {
+        let mut hashset = CellSet::default();
+        for y in 0..height {
+            for x in 0..width {
+                if cells[(y * width + x) as usize] {
+                    hashset.insert((x, y));
+                }
+            }
+        }
+        hashset
+    }
+
+    pub fn tick(&mut self) {
+        let start = Instant::now();
+        self.advance();
+        let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+        self.avg_tick_ms = Some(match self.avg_tick_ms {
+            Some(average) => average + TICK_AVERAGE_WEIGHT * (elapsed_ms - average),
+            None => elapsed_ms,
+        });
+        self.notify_tick(elapsed_ms);
+    }
+
+    /// Exponential moving average of how long `tick` takes, in milliseconds, weighting
+    /// the latest tick by `TICK_AVERAGE_WEIGHT` so a displayed value changes smoothly.
+    /// 0 before the first tick.
+    pub fn avg_tick_ms(&self) -> f64 {
+        self.avg_tick_ms.unwrap_or(0.0)
+    }
+
+    /// Ticks per second `avg_tick_ms` allows, for an FPS counter. 0 before the first tick.
+    pub fn estimated_fps(&self) -> f64 {
+        match self.avg_tick_ms {
+            Some(average) if average > 0.0 => 1000.0 / average,
+            _ => 0.0,
+        }
+    }
+
+    /// Ticks once and returns which cells were born and which died, so a renderer can
+    /// animate them differently. Cells edited since the previous tick count as well, like
+    /// in `changed_cells`.
+    pub fn tick_events(&mut self) -> TickEvents {
+        self.tick();
+        let (births, deaths) = self
+            .changed_cells()
+            .into_iter()
+            .partition(|&index| self.get_cell(index / self.width, index % self.width));
+        TickEvents { births, deaths }
+    }
+
+    /// Advances `generations` generations without per-tick callbacks, firing the
+    /// on-tick callback once at the end with the final stats. A verbose universe likewise
+    /// logs once, with the duration of the whole batch. Stops early once the universe has
+    /// died out for good, see `generation` for how far it got.
+    pub fn fast_forward(&mut self, generations: u32) {
+        let start = Instant::now();
+        for _ in 0..generations {
+            if self.is_dead_forever() {
+                break;
+            }
+            self.advance();
+        }
+        if generations > 0 {
+            self.notify_tick(start.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+
+    /// Whether the current state is a fixed point. Computes one generation ahead and
+    /// restores the current state afterwards, the generation counter is not advanced.
+    pub fn is_still_life(&mut self) -> bool {
+        let decay = self.decay.clone();
+        // The tick moves the current generation into the previous one's buffer, so moving
+        // it back restores the state, and the saved buffer restores `changed_cells`.
+        let changed = match self.implementation {
+            Implementation::HashSet => {
+                let previous = self.previous_cells_hashset.take();
+                let changed = self.next();
+                self.cells_hashset = std::mem::replace(&mut self.previous_cells_hashset, previous)
+                    .expect("a tick keeps the previous generation");
+                changed
+            }
+            _ => {
+                // The second-order rule reads the previous generation, so it is copied
+                let previous = match self.implementation {
+                    Implementation::SecondOrder => self.previous_cells.clone(),
+                    _ => std::mem::take(&mut self.previous_cells),
+                };
+                let changed = self.next();
+                self.cells = std::mem::replace(&mut self.previous_cells, previous);
+                changed
+            }
+        };
+        self.decay = decay;
+        !changed
+    }
+
+    /// Steps the SecondOrder implementation one generation back: since the next generation
+    /// is `life(current) XOR previous`, the one before the previous is recovered as
+    /// `life(previous) XOR current`. Returns `false` without changing anything for the
+    /// other implementations, which are not reversible, and at generation 0.
+    pub fn tick_back(&mut self) -> bool {
+        if !matches!(self.implementation, Implementation::SecondOrder) || self.generation == 0 {
+            return false;
+        }
+        self.debug_check_invariants();
+        let mut previous = std::mem::take(&mut self.previous_cells);
+        previous.resize(self.cells.len(), false);
+        let current = std::mem::replace(&mut self.cells, previous);
+        self.next_naive();
+        for (cell, &was_alive) in self.cells.iter_mut().zip(&current) {
+            *cell ^= was_alive;
+        }
+        std::mem::swap(&mut self.cells, &mut self.previous_cells);
+        self.generation -= 1;
+        true
+    }
+
+    /// Ticks `n` times and returns the population after each tick.
+    pub fn tick_batch(&mut self, n: u32) -> Vec<u32> {
+        (0..n)
+            .map(|_| {
+                self.tick();
+                self.population()
+            })
+            .collect()
+    }
+
+    /// Registers a callback invoked with the stats of the universe after each tick.
+    pub fn set_on_tick(&mut self, callback: impl FnMut(TickStats) + Send + Sync + 'static) {
+        self.on_tick = Some(Box::new(callback));
+    }
+
+    pub fn clear_on_tick(&mut self) {
+        self.on_tick = None;
+    }
+
+    /// Logs a line per tick with the generation, population and duration, see
+    /// `TickStats::log_line`, to the browser console with the `wasm` feature and to stderr
+    /// otherwise. Off by default, counting the population takes a pass over the grid.
+    pub fn set_verbose(&mut self, enabled: bool) {
+        self.verbose = enabled;
+    }
+
+    fn notify_tick(&mut self, elapsed_ms: f64) {
+        if self.on_tick.is_some() || self.verbose {
+            let stats = TickStats {
+                generation: self.generation,
+                population: self.population(),
+            };
+            if self.verbose {
+                log_message(&stats.log_line(elapsed_ms));
+            }
+            if let Some(callback) = self.on_tick.as_mut() {
+                callback(stats);
+            }
+        }
+    }
+
+    fn advance(&mut self) {
+        self.previous_population = Some(self.population());
+        let previous = self.transitions.map(|_| (self.live_mask(), self.neighbor_counts()));
+        let changed = self.next();
+        if let Some((previous, previous_counts)) = previous {
+            self.transitions = Some(self.count_transitions(&previous, &previous_counts));
+        }
+        self.debug_check_invariants();
+        self.generation += 1;
+        if changed {
+            self.last_change_generation = self.generation;
+        }
+
+        let population = self.population_history.as_ref().map(|_| self.population());
+        if let (Some(history), Some(population)) = (&mut self.population_history, population) {
+            if history.len() == POPULATION_HISTORY_LIMIT {
+                history.pop_front();
+            }
+            history.push_back(population);
+        }
+    }
+
+    /// Row-major flat indices, in increasing order, of the cells whose live/dead state
+    /// differs from the generation before the last tick. Edits made since that tick count
+    /// too. Empty before the first tick and after a resize or reset.
+    pub fn changed_cells(&self) -> Vec<u32> {
+        match self.implementation {
+            Implementation::HashSet => {
+                let Some(previous) = &self.previous_cells_hashset else {
+                    return Vec::new();
+                };
+                let mut changed: Vec<u32> =
+                    self.cells_hashset.symmetric_difference(previous).map(|&(x, y)| y * self.width + x).collect();
+                changed.sort_unstable();
+                changed
+            }
+            _ if self.previous_cells.len() != self.cells.len() => Vec::new(),
+            _ => (0..self.cells.len() as u32)
+                .filter(|&index| self.cells[index as usize] != self.previous_cells[index as usize])
+                .collect(),
+        }
+    }
+
+    /// Whether the cell at `(row, col)` was alive in the generation before the last tick,
+    /// its current state when there is none, see `changed_cells`.
+    fn was_alive(&self, row: u32, col: u32) -> bool {
+        if !self.in_bounds(row, col) {
+            return false;
+        }
+        match self.implementation {
+            Implementation::HashSet => match &self.previous_cells_hashset {
+                Some(previous) => previous.contains(&(col, row)),
+                None => self.get_cell(row, col),
+            },
+            _ if self.previous_cells.len() != self.cells.len() => self.get_cell(row, col),
+            _ => self.previous_cells[(row * self.width + col) as usize],
+        }
+    }
+
+    /// Liveness of the cell at `(row, col)` blended between the generation before the last
+    /// tick, at `alpha` 0, and the current one, at `alpha` 1, for cross-fading births and
+    /// deaths when rendering between generations. `alpha` is clamped to that range.
+    pub fn cell_fade(&self, row: u32, col: u32, alpha: f64) -> f64 {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let previous = self.was_alive(row, col) as u8 as f64;
+        let current = self.get_cell(row, col) as u8 as f64;
+        previous + (current - previous) * alpha
+    }
+
+    /// Returns the `(row, col)` coordinates, in row-major order, where the live/dead state
+    /// of `self` and `other` differ. Both universes must have the same dimensions.
+    pub fn diff(&self, other: &Universe) -> Result<Vec<(u32, u32)>, DimensionMismatch> {
+        if self.width != other.width || self.height != other.height {
+            return Err(DimensionMismatch {
+                expected: (self.width, self.height),
+                found: (other.width, other.height),
+            });
+        }
+
+        let mut differences = Vec::new();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.get_cell(row, col) != other.get_cell(row, col) {
+                    differences.push((row, col));
+                }
+            }
+        }
+        Ok(differences)
+    }
+
+    /// Whether both universes have the same dimensions and live cells, whichever
+    /// implementation each uses. Each side's live cells are gathered once and compared
+    /// directly, hashing them first would only add work.
+    pub fn equals(&self, other: &Universe) -> bool {
+        self.width == other.width && self.height == other.height && self.live_cells() == other.live_cells()
+    }
+
+    /// Asserts that the top-left region of the grid matches `ascii`, see
+    /// [`Universe::assert_eq_pattern_at`].
+    #[track_caller]
+    pub fn assert_eq_pattern(&self, ascii: &str) {
+        self.assert_eq_pattern_at(0, 0, ascii);
+    }
+
+    /// Asserts that the region starting at `(row, col)` matches `ascii`, a multi-line
+    /// `.`/`O` picture. Lines are trimmed and blank lines skipped, so an indented raw string
+    /// works. Cells outside of the grid count as dead. On mismatch it panics with the
+    /// expected and actual region side by side and the differing cells marked with `X`.
+    #[track_caller]
+    pub fn assert_eq_pattern_at(&self, row: u32, col: u32, ascii: &str) {
+        let expected: Vec<&str> = ascii.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        let mut report = String::new();
+        let mut mismatches = 0;
+        for (row_offset, line) in expected.iter().enumerate() {
+            let (mut actual, mut markers) = (String::new(), String::new());
+            for (col_offset, char) in line.chars().enumerate() {
+                let expected_alive = match char {
+                    'O' => true,
+                    '.' => false,
+                    char => panic!("Unexpected character '{}' in pattern line {}", char, row_offset + 1),
+                };
+                let alive = self.get_cell(row + row_offset as u32, col + col_offset as u32);
+                actual.push(if alive { 'O' } else { '.' });
+                markers.push(if alive == expected_alive { ' ' } else { 'X' });
+                if alive != expected_alive {
+                    mismatches += 1;
+                }
+            }
+            report.push_str(format!("{}  {}  {}", line, actual, markers).trim_end());
+            report.push('\n');
+        }
+        if mismatches > 0 {
+            panic!(
+                "Pattern at ({}, {}) differs in {} cell(s), expected, actual and differences:\n{}",
+                row, col, mismatches, report
+            );
+        }
+    }
+
+    /// Fraction of the live cells on the outermost ring of the grid, 0 without live cells.
+    /// A high value means the pattern reaches the edges, where wrapping around or dead
+    /// edges change its evolution, so the grid may be too small for it.
+    pub fn edge_activity(&self) -> f64 {
+        let live = self.live_cells();
+        if live.is_empty() {
+            return 0.0;
+        }
+        let on_edge = live
+            .iter()
+            .filter(|&&(row, col)| row == 0 || col == 0 || row == self.height - 1 || col == self.width - 1)
+            .count();
+        on_edge as f64 / live.len() as f64
+    }
+
+    /// Returns the `(row, col)` coordinates of all live cells in row-major order.
+    pub fn live_cells(&self) -> Vec<(u32, u32)> {
+        let mut cells = Vec::new();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.get_cell(row, col) {
+                    cells.push((row, col));
+                }
+            }
+        }
+        cells
+    }
+
+    /// Returns the smallest `(min_row, min_col, max_row, max_col)` rectangle containing
+    /// all live cells, or `None` if the universe is empty. Wrapping is not taken into
+    /// account, so a pattern straddling an edge spans the whole grid.
+    pub fn bounding_box(&self) -> Option<(u32, u32, u32, u32)> {
+        self.live_cells().into_iter().fold(None, |bounds, (row, col)| match bounds {
+            None => Some((row, col, row, col)),
+            Some((min_row, min_col, max_row, max_col)) => {
+                Some((min_row.min(row), min_col.min(col), max_row.max(row), max_col.max(col)))
+            }
+        })
+    }
+
+    /// Renders the bounding box of the live cells as plaintext (`.`/`O`, one line per row),
+    /// without the dimensions header. An empty universe yields an empty string.
+    pub fn trimmed_string(&self) -> String {
+        let mut text = String::new();
+        if let Some((min_row, min_col, max_row, max_col)) = self.bounding_box() {
+            for row in min_row..=max_row {
+                for col in min_col..=max_col {
+                    text.push(if self.get_cell(row, col) { 'O' } else { '.' });
+                }
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Returns a new universe sized to the bounding box of the live cells, with the pattern
+    /// moved to the top-left. It uses the same implementation, rule and boundary mode and
+    /// starts at generation 0. An empty universe yields a 1x1 universe with a dead cell.
+    pub fn trimmed(&self) -> Universe {
+        let (min_row, min_col, max_row, max_col) = self.bounding_box().unwrap_or((0, 0, 0, 0));
+        let mut trimmed = Universe::empty(max_col - min_col + 1, max_row - min_row + 1, self.implementation);
+        for (row, col) in self.live_cells() {
+            trimmed.set_cell(row - min_row, col - min_col, true);
+        }
+        (trimmed.rule, trimmed.isotropic_rule) = (self.rule, self.isotropic_rule);
+        trimmed.set_states(self.states);
+        trimmed.boundary = self.boundary;
+        trimmed.remember_seed();
+        trimmed
+    }
+
+    /// Reports which reflections leave the live pattern unchanged within its bounding box.
+    /// An empty universe is trivially symmetric.
+    pub fn symmetry(&self) -> Symmetry {
+        let (min_row, min_col, max_row, max_col) = match self.bounding_box() {
+            Some(bounds) => bounds,
+            None => return Symmetry { horizontal: true, vertical: true, diagonal: true },
+        };
+        let height = max_row - min_row;
+        let width = max_col - min_col;
+        let cells: HashSet<(u32, u32)> = self
+            .live_cells()
+            .into_iter()
+            .map(|(row, col)| (row - min_row, col - min_col))
+            .collect();
+        let invariant = |reflect: &dyn Fn(u32, u32) -> (u32, u32)| {
+            cells.iter().all(|&(row, col)| cells.contains(&reflect(row, col)))
+        };
+
+        Symmetry {
+            horizontal: invariant(&|row, col| (height - row, col)),
+            vertical: invariant(&|row, col| (row, width - col)),
+            diagonal: height == width
+                && (invariant(&|row, col| (col, row)) || invariant(&|row, col| (width - col, height - row))),
+        }
+    }
+
+    /// Approximate number of free-flying gliders: 3x3 windows holding one of the glider's
+    /// 4 phases in any of its 4 directions, with no other live cell touching the window.
+    /// Gliders straddling an edge of a toroidal universe are not counted.
+    pub fn glider_count(&self) -> u32 {
+        let templates = Universe::glider_templates();
+        let is_alive = |row: i64, col: i64| row >= 0 && col >= 0 && self.get_cell(row as u32, col as u32);
+        let mut count = 0;
+        for row in 0..self.height.saturating_sub(2) as i64 {
+            for col in 0..self.width.saturating_sub(2) as i64 {
+                let mut mask = 0u16;
+                for bit in 0..9 {
+                    if is_alive(row + bit / 3, col + bit % 3) {
+                        mask |= 1 << bit;
+                    }
+                }
+                if !templates.contains(&mask) {
+                    continue;
+                }
+                let isolated = (row - 1..=row + 3)
+                    .flat_map(|border_row| (col - 1..=col + 3).map(move |border_col| (border_row, border_col)))
+                    .filter(|&(border_row, border_col)| {
+                        !(row..row + 3).contains(&border_row) || !(col..col + 3).contains(&border_col)
+                    })
+                    .all(|(border_row, border_col)| !is_alive(border_row, border_col));
+                if isolated {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// The glider's phases and directions as bitmasks of its 3x3 bounding box, bit
+    /// `row * 3 + col` set for a live cell.
+    fn glider_templates() -> Vec<u16> {
+        let mut universe = Universe::empty(8, 8, Implementation::Naive);
+        universe.insert_named_pattern("glider", 2, 2);
+        let mut templates = Vec::new();
+        for _ in 0..4 {
+            let phase = universe.trimmed().live_cells();
+            for (flip_rows, flip_cols) in [(false, false), (false, true), (true, false), (true, true)] {
+                let mask = phase.iter().fold(0u16, |mask, &(row, col)| {
+                    let row = if flip_rows { 2 - row } else { row };
+                    let col = if flip_cols { 2 - col } else { col };
+                    mask | 1 << (row * 3 + col)
+                });
+                if !templates.contains(&mask) {
+                    templates.push(mask);
+                }
+            }
+            universe.tick();
+        }
+        templates
+    }
+
+    /// Groups the live cells into connected components, through the 4 orthogonal neighbors
+    /// or, if `diagonal`, all 8. Components connect across the edges of a toroidal universe.
+    /// They come in row-major order of their first cell, which is listed first.
+    pub fn connected_components(&self, diagonal: bool) -> Vec<Vec<(u32, u32)>> {
+        let (height, width) = (self.height as i64, self.width as i64);
+        let live: HashSet<(u32, u32)> = self.live_cells().into_iter().collect();
+        let mut visited: HashSet<(u32, u32)> = HashSet::new();
+        let mut components = Vec::new();
+        for cell in self.live_cells() {
+            if !visited.insert(cell) {
+                continue;
+            }
+            let mut component = vec![cell];
+            let mut next = 0;
+            while let Some(&(row, col)) = component.get(next) {
+                next += 1;
+                for delta_row in -1..=1 {
+                    for delta_col in -1..=1 {
+                        if (delta_row == 0 && delta_col == 0) || (!diagonal && delta_row != 0 && delta_col != 0) {
+                            continue;
+                        }
+
+                        let (neighbor_row, neighbor_col) = (row as i64 + delta_row, col as i64 + delta_col);
+                        let neighbor = match self.boundary {
+                            BoundaryMode::Toroidal => {
+                                let (neighbor_col, neighbor_row) = self.wrap(neighbor_col, neighbor_row);
+                                (neighbor_row, neighbor_col)
+                            }
+                            BoundaryMode::Dead => {
+                                if !(0..height).contains(&neighbor_row) || !(0..width).contains(&neighbor_col) {
+                                    continue;
+                                }
+                                (neighbor_row as u32, neighbor_col as u32)
+                            }
+                        };
+                        if live.contains(&neighbor) && visited.insert(neighbor) {
+                            component.push(neighbor);
+                        }
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// Names each object, an 8-connected component of live cells, with `Pattern::classify`,
+    /// `None` for unknown ones. A beacon only counts as one object in the phase where its
+    /// two blocks touch, and an object wrapping around a toroidal edge is not recognized.
+    pub fn classify_objects(&self) -> Vec<Option<&'static str>> {
+        self.connected_components(true).into_iter().map(|cells| Pattern::new(cells).classify()).collect()
+    }
+
+    /// Hash of the live cells, equal for equal states regardless of the implementation.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.width, self.height).hash(&mut hasher);
+        self.live_cells().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Ticks until the state repeats, or at most `max_generations` times. Besides still lifes
+    /// and oscillators, this detects spaceships by comparing each generation's pattern
+    /// relative to its bounding box, so the reported displacement is only meaningful while
+    /// the pattern does not straddle an edge. States are compared by hash.
+    pub fn run_until_stable(&mut self, max_generations: u32) -> Stability {
+        self.settle(max_generations, |_| {}).0
+    }
+
+    /// Runs a methuselah such as the R-pentomino until it settles, or at most
+    /// `max_generations` times, and reports its peak population, when it settled and what
+    /// into. Settling is detected like in `run_until_stable`, so on a torus escaping
+    /// gliders keep the pattern from settling until they crash into the debris, and with
+    /// dead edges they settle once they have hit an edge. Either way the grid needs to be
+    /// much larger than the pattern for results close to an infinite plane.
+    pub fn analyze_methuselah(&mut self, max_generations: u32) -> MethuselahReport {
+        let mut peak = (0, self.generation);
+        let (stability, settled_at) = self.settle(max_generations, |universe| {
+            let population = universe.population();
+            if population > peak.0 {
+                peak = (population, universe.generation);
+            }
+        });
+        MethuselahReport {
+            peak_population: peak.0,
+            peak_generation: peak.1,
+            settled_at,
+            stability,
+            final_population: self.population(),
+        }
+    }
+
+    /// Implements `run_until_stable`, calling `observe` with every generation looked at and
+    /// also returning the generation the repeating state or shape first appeared in.
+    fn settle(&mut self, max_generations: u32, mut observe: impl FnMut(&Universe)) -> (Stability, Option<u64>) {
+        let mut states: HashMap<u64, u64> = HashMap::new();
+        let mut shapes: HashMap<u64, (u64, u32, u32)> = HashMap::new();
+
+        for step in 0..=max_generations {
+            observe(self);
+            let generation = self.generation;
+            if let Some(previous) = states.insert(self.state_hash(), generation) {
+                let stability = match generation - previous {
+                    1 => Stability::StillLife,
+                    period => Stability::Oscillator { period },
+                };
+                return (stability, Some(previous));
+            }
+
+            if let Some((min_row, min_col, _, _)) = self.bounding_box() {
+                let mut hasher = DefaultHasher::new();
+                for (row, col) in self.live_cells() {
+                    (row - min_row, col - min_col).hash(&mut hasher);
+                }
+                if let Some((previous, previous_row, previous_col)) =
+                    shapes.insert(hasher.finish(), (generation, min_row, min_col))
+                {
+                    let stability = Stability::Spaceship {
+                        period: generation - previous,
+                        dx: min_col as i64 - previous_col as i64,
+                        dy: min_row as i64 - previous_row as i64,
+                    };
+                    return (stability, Some(previous));
+                }
+            }
+
+            if step < max_generations {
+                self.tick();
+            }
+        }
+        (Stability::Unsettled, None)
+    }
+
+    /// Number of cells the live set of the HashSet implementation has room for without
+    /// reallocating, 0 for the array implementations, which do not populate it.
+    pub fn hashset_capacity(&self) -> usize {
+        self.cells_hashset.capacity()
+    }
+
+    /// Measures the memory the cells currently take, unlike the `estimated_memory` lower
+    /// bound, including how far the live set is over-allocated.
+    pub fn memory_estimate(&self) -> MemoryEstimate {
+        let set_capacity = self.cells_hashset.capacity() + self.previous_cells_hashset.as_ref().map_or(0, |set| set.capacity());
+        MemoryEstimate {
+            bytes: (self.cells.capacity() + self.previous_cells.capacity()) as u64
+                + set_capacity as u64 * (HASHSET_BYTES_PER_LIVE_CELL / 4),
+            live_cells: self.population(),
+            hashset_capacity: self.cells_hashset.capacity(),
+        }
+    }
+
+    /// Releases memory the cell buffers no longer need, e.g. the hashset's capacity
+    /// after a large population died back. The previous generation's buffers, kept for
+    /// the double buffering, are shrunk as well.
+    pub fn shrink_to_fit(&mut self) {
+        self.cells_hashset.shrink_to_fit();
+        self.cells.shrink_to_fit();
+        self.previous_cells.shrink_to_fit();
+        if let Some(previous) = &mut self.previous_cells_hashset {
+            previous.shrink_to_fit();
+        }
+    }
+
+    /// Ticks once and reports whether the pattern settled, for callers that animate the
+    /// universe and want to stop once nothing new happens: 0 if the new state was not seen
+    /// recently, 1 if the tick changed nothing (a still life, including an empty universe)
+    /// and 2 if it repeats one of the last `STATE_HISTORY_LIMIT` states (an oscillator).
+    /// States are compared by `state_hash`. The history restarts when the universe was
+    /// ticked some other way in between.
+    pub fn tick_checked(&mut self) -> u8 {
+        if self.recent_states.back().is_none_or(|&(generation, _)| generation != self.generation) {
+            self.recent_states.clear();
+            self.recent_states.push_back((self.generation, self.state_hash()));
+        }
+
+        self.tick();
+        let hash = self.state_hash();
+        let code = match self.recent_states.iter().rposition(|&(_, previous)| previous == hash) {
+            Some(position) if position + 1 == self.recent_states.len() => 1,
+            Some(_) => 2,
+            None => 0,
+        };
+        if self.recent_states.len() == STATE_HISTORY_LIMIT {
+            self.recent_states.pop_front();
+        }
+        self.recent_states.push_back((self.generation, hash));
+        code
+    }
+
+    /// Ticks up to `max` generations and returns the period of the first cycle reached,
+    /// allowing for any transient before it. States are compared exactly rather than by
+    /// hash, which suits small patterns. Returns `None` if no state repeats within `max`.
+    pub fn find_period(&mut self, max: u32) -> Option<u32> {
+        let mut seen: HashMap<Vec<(u32, u32)>, u32> = HashMap::new();
+        for step in 0..=max {
+            if let Some(previous) = seen.insert(self.live_cells(), step) {
+                return Some(step - previous);
+            }
+            if step < max {
+                self.tick();
+            }
+        }
+        None
+    }
+
+    pub fn set_running(&mut self, running: bool) {
+        self.running = running;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Sets the ticks per second targeted by `update`.
+    pub fn set_tps(&mut self, tps: f64) {
+        self.tps = tps;
+    }
+
+    pub fn tps(&self) -> f64 {
+        self.tps
+    }
+
+    /// Advances the animation by `dt_ms` milliseconds of wall time, ticking as many times
+    /// as `tps` allows and carrying the remainder over to the next call.
+    /// Returns the number of ticks performed, which is 0 while paused.
+    pub fn update(&mut self, dt_ms: f64) -> u32 {
+        if !self.running || self.tps <= 0.0 {
+            return 0;
+        }
+
+        let tick_interval_ms = 1000.0 / self.tps;
+        self.accumulated_ms += dt_ms;
+        let ticks = (self.accumulated_ms / tick_interval_ms) as u32;
+        self.accumulated_ms -= ticks as f64 * tick_interval_ms;
+
+        for _ in 0..ticks {
+            self.tick();
+        }
+        ticks
+    }
+
+    /// Ticks repeatedly for roughly `budget_ms` milliseconds and returns the number of ticks
+    /// performed, so long runs can yield to an event loop between batches. Always ticks at
+    /// least once, and stops early when another tick as slow as the slowest one so far
+    /// would exceed the budget. Under the `wasm` feature the time comes from
+    /// `performance.now()`, so it works in the browser.
+    pub fn step_within(&mut self, budget_ms: f64) -> u32 {
+        let budget = Duration::from_secs_f64(budget_ms.max(0.0) / 1000.0);
+        let start = Instant::now();
+        let mut slowest = Duration::ZERO;
+        let mut ticks = 0;
+
+        loop {
+            let tick_start = Instant::now();
+            self.tick();
+            slowest = slowest.max(tick_start.elapsed());
+            ticks += 1;
+            if start.elapsed() + slowest > budget {
+                return ticks;
+            }
+        }
+    }
+
+    /// Ticks `iterations` times, timing every tick individually.
+    pub fn profile(&mut self, iterations: u32) -> ProfileReport {
+        let mut durations: Vec<Duration> = (0..iterations)
+            .map(|_| {
+                let start = Instant::now();
+                self.tick();
+                start.elapsed()
+            })
+            .collect();
+        if durations.is_empty() {
+            return ProfileReport::default();
+        }
+
+        durations.sort();
+        let middle = durations.len() / 2;
+        let median = if durations.len().is_multiple_of(2) {
+            (durations[middle - 1] + durations[middle]) / 2
+        } else {
+            durations[middle]
+        };
+
+        ProfileReport {
+            count: iterations,
+            min: durations[0],
+            max: durations[durations.len() - 1],
+            mean: durations.iter().sum::<Duration>() / iterations,
+            median,
+        }
+    }
+
+    pub fn game_of_life(&mut self, iterations: u32) -> u32 {
+        self.game_of_life_with(iterations, |_| {})
+    }
+
+    /// Runs up to `iterations` generations without printing anything, stopping early once
+    /// the universe can no longer change. Returns the number of generations run.
+    pub fn run_headless(&mut self, iterations: u32) -> u32 {
+        let mut completed = 0;
+        while completed < iterations && !self.is_dead_forever() {
+            self.tick();
+            completed += 1;
+        }
+        completed
+    }
+
+    /// Like `game_of_life`, calling `on_generation` with the initial state and again after
+    /// every tick, e.g. to write out animation frames. Both stop early once the universe
+    /// has died out for good and return the number of iterations actually run.
+    pub fn game_of_life_with(&mut self, iterations: u32, mut on_generation: impl FnMut(&Universe)) -> u32 {
+        let start = Instant::now();
+
+        on_generation(self);
+        let mut completed = 0;
+        while completed < iterations && !self.is_dead_forever() {
+            self.tick();
+            on_generation(self);
+            completed += 1;
+        }
+
+        let duration = start.elapsed();
+        if completed < iterations {
+            println!("The universe died out after {} of {} iterations", completed, iterations);
+        }
+        println!("{} iterations took {:?} ms using the {:?} implementation", completed, duration.as_millis(), self.implementation);
+        completed
+    }
+
+    /// Computes the next generation with the active implementation, returning whether
+    /// any cell changed.
+    fn next(&mut self) -> bool {
+        let previous = (self.states > 2).then(|| self.live_mask());
+        let changed = match self.implementation {
+            Implementation::Naive => self.next_naive(),
+            Implementation::HashSet => self.next_hashset(),
+            Implementation::Parallel => self.next_parallel(),
+            Implementation::SecondOrder => self.next_second_order(),
+            Implementation::Simd => self.next_simd(),
+        };
+        match previous {
+            Some(previous) => self.advance_decay(&previous) || changed,
+            None => changed,
+        }
+    }
+
+    /// Whether each cell is alive, in row-major order.
+    fn live_mask(&self) -> Vec<bool> {
+        match self.implementation {
+            Implementation::HashSet => (0..self.height)
+                .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+                .map(|(row, col)| self.get_cell(row, col))
+                .collect(),
+            _ => self.cells.clone(),
+        }
+    }
+
+    /// Applies the Generations states on top of a tick computed by the rule alone, given
+    /// which cells were alive before it. Dying cells ignore the rule's births and move on to
+    /// the next state, live cells that did not survive start dying. Returns whether any
+    /// cell was dying.
+    fn advance_decay(&mut self, previous: &[bool]) -> bool {
+        let mut dying = false;
+        for (index, &was_alive) in previous.iter().enumerate() {
+            let (row, col) = (index as u32 / self.width, index as u32 % self.width);
+            let state = self.decay[index];
+            if state > 0 {
+                self.set_cell(row, col, false);
+                self.decay[index] = if state + 1 < self.states { state + 1 } else { 0 };
+                dying = true;
+            } else if was_alive && !self.get_cell(row, col) {
+                self.decay[index] = 2;
+            }
+        }
+        dying
+    }
+
+    /// Whether the array implementations can count neighbors a row at a time. The per-cell
+    /// path is kept for the precomputed neighbor table, for grids thinner than 3 cells and
+    /// for a sheared torus.
+    fn uses_row_counts(&self) -> bool {
+        self.neighbor_table.is_none() && self.width >= 3 && self.height >= 3 && !self.is_sheared()
+    }
+
+    /// Fills `counts` with the live neighbor count of every cell in row `y`. The three rows
+    /// around it are first summed column by column into `sums`, then each count is the sum
+    /// of three adjacent columns minus the cell itself. Both passes are plain slice
+    /// arithmetic that the compiler vectorizes.
+    fn row_neighbor_counts(&self, y: usize, sums: &mut [u8], counts: &mut [u8]) {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let toroidal = self.boundary == BoundaryMode::Toroidal;
+        let row_at = |y: usize| &self.cells[y * width..(y + 1) * width];
+
+        let row = row_at(y);
+        let (above, below) = if toroidal {
+            (Some(row_at((y + height - 1) % height)), Some(row_at((y + 1) % height)))
+        } else {
+            (y.checked_sub(1).map(row_at), (y + 1 < height).then(|| row_at(y + 1)))
+        };
+
+        for (sum, &cell) in sums.iter_mut().zip(row) {
+            *sum = cell as u8;
+        }
+        for neighbor_row in [above, below].into_iter().flatten() {
+            for (sum, &cell) in sums.iter_mut().zip(neighbor_row) {
+                *sum += cell as u8;
+            }
+        }
+
+        for (count, (window, &cell)) in counts[1..width - 1].iter_mut().zip(sums.windows(3).zip(&row[1..width - 1])) {
+            *count = window[0] + window[1] + window[2] - cell as u8;
+        }
+        let (left_edge, right_edge) = if toroidal { (sums[width - 1], sums[0]) } else { (0, 0) };
+        counts[0] = left_edge + sums[0] + sums[1] - row[0] as u8;
+        counts[width - 1] = sums[width - 2] + sums[width - 1] + right_edge - row[width - 1] as u8;
+    }
+
+    /// Applies the rule to row `y` given its neighbor `counts`. The custom rule function is
+    /// checked once per row rather than per cell, and the rule is indexed by the cell's
+    /// state instead of branching on it, which random soups would keep mispredicting.
+    fn next_row_states(&self, y: usize, counts: &[u8], next_row: &mut [bool]) {
+        let width = self.width as usize;
+        let row = &self.cells[y * width..(y + 1) * width];
+        let cells = next_row.iter_mut().zip(row).zip(counts);
+        match &self.rule_fn {
+            Some(rule_fn) => cells.for_each(|((next_cell, &cell), &count)| *next_cell = rule_fn(cell, count as NeighborCount)),
+            None => {
+                let table = [self.rule.birth, self.rule.survival];
+                cells.for_each(|((next_cell, &cell), &count)| *next_cell = table[cell as usize][count as usize]);
+            }
+        }
+    }
+
+    /// Computes the next generation, returning whether any cell changed.
+    fn next_naive(&mut self) -> bool {
+        if !self.uses_row_counts() || self.uses_neighbor_masks() {
+            return self.next_naive_per_cell();
+        }
+
+        let width = self.width as usize;
+        let (mut next, reused) = self.take_back_buffer();
+        let mut sums = vec![0; width];
+        let mut counts = vec![0; width];
+        for (y, next_row) in next.chunks_mut(width).enumerate() {
+            self.row_neighbor_counts(y, &mut sums, &mut counts);
+            self.next_row_states(y, &counts, next_row);
+        }
+        let changed = next != self.cells;
+        self.previous_cells = std::mem::replace(&mut self.cells, next);
+        let cells = self.cells.len() as u64;
+        self.record_profile(2 + !reused as u64, reused as u64, cells, 3 * cells);
+        changed
+    }
+
+    /// Computes the next generation like `next_naive`, 64 cells at a time. Every row is
+    /// packed into words along with copies shifted one cell west and east, wrapping around
+    /// on a torus, so a word's 8 neighbor planes are plain words of the rows around it.
+    /// Adding them with full adders yields the neighbor count in four bit planes, which are
+    /// matched against the counts in the rule.
+    fn next_simd(&mut self) -> bool {
+        if self.rule_fn.is_some() || !self.uses_row_counts() || self.uses_neighbor_masks() {
+            return self.next_naive();
+        }
+
+        let (width, height) = (self.width as usize, self.height as usize);
+        let words = width.div_ceil(64);
+        let toroidal = self.boundary == BoundaryMode::Toroidal;
+        let mut packed = vec![0u64; words * height];
+        for (row_words, row) in packed.chunks_mut(words).zip(self.cells.chunks(width)) {
+            for (word, cells) in row_words.iter_mut().zip(row.chunks(64)) {
+                *word = Universe::pack_word(cells);
+            }
+        }
+        let (mut west, mut east) = (vec![0u64; packed.len()], vec![0u64; packed.len()]);
+        for ((row, west_row), east_row) in packed.chunks(words).zip(west.chunks_mut(words)).zip(east.chunks_mut(words)) {
+            Universe::shift_packed_row(row, width, toroidal, west_row, east_row);
+        }
+
+        // Cells whose count is n become alive if it is a birth count and they are dead, or a
+        // survival count and they are alive
+        let all_or_none = |set: bool| if set { u64::MAX } else { 0 };
+        let rule_counts: Vec<([u64; 4], u64, u64)> = (0..9)
+            .filter(|&count| self.rule.birth[count] || self.rule.survival[count])
+            .map(|count| {
+                let planes = [1, 2, 4, 8].map(|weight| all_or_none(count & weight != 0));
+                (planes, all_or_none(self.rule.birth[count]), all_or_none(self.rule.survival[count]))
+            })
+            .collect();
+
+        let (mut next, reused) = self.take_back_buffer();
+        let mut next_words = vec![0u64; words];
+        let mut changed = false;
+        let zeros = vec![0u64; words];
+        for (y, next_row) in next.chunks_mut(width).enumerate() {
+            let neighbor_rows = |y: Option<usize>| match y {
+                Some(y) => {
+                    let range = y * words..(y + 1) * words;
+                    (&packed[range.clone()], &west[range.clone()], &east[range])
+                }
+                None => (&zeros[..], &zeros[..], &zeros[..]),
+            };
+            let (above, below) = if toroidal {
+                (Some((y + height - 1) % height), Some((y + 1) % height))
+            } else {
+                (y.checked_sub(1), (y + 1 < height).then_some(y + 1))
+            };
+            let (north, north_west, north_east) = neighbor_rows(above);
+            let (south, south_west, south_east) = neighbor_rows(below);
+            let (row, row_west, row_east) = neighbor_rows(Some(y));
+
+            for (i, next_word) in next_words.iter_mut().enumerate() {
+                let full_add = |a: u64, b: u64, c: u64| (a ^ b ^ c, (a & b) | (c & (a ^ b)));
+                let (sum_a, carry_a) = full_add(north_west[i], north[i], north_east[i]);
+                let (sum_b, carry_b) = full_add(row_west[i], row_east[i], south_west[i]);
+                let (sum_c, carry_c) = (south[i] ^ south_east[i], south[i] & south_east[i]);
+                let (ones, carry_d) = full_add(sum_a, sum_b, sum_c);
+                let (twos_partial, carry_e) = full_add(carry_a, carry_b, carry_c);
+                let (twos, carry_f) = (twos_partial ^ carry_d, twos_partial & carry_d);
+                let (fours, eights) = (carry_e ^ carry_f, carry_e & carry_f);
+
+                let alive = row[i];
+                let mut word = 0;
+                for &([one, two, four, eight], birth, survival) in &rule_counts {
+                    let matches = !((ones ^ one) | (twos ^ two) | (fours ^ four) | (eights ^ eight));
+                    word |= matches & ((!alive & birth) | (alive & survival));
+                }
+                *next_word = word;
+            }
+            // Bits past the width belong to no cell
+            if width % 64 != 0 {
+                next_words[words - 1] &= (1 << (width % 64)) - 1;
+            }
+            changed |= next_words[..] != row[..];
+            for (cells, &word) in next_row.chunks_mut(64).zip(&next_words) {
+                Universe::unpack_word(word, cells);
+            }
+        }
+        self.previous_cells = std::mem::replace(&mut self.cells, next);
+        let cells = self.cells.len() as u64;
+        self.record_profile(5 + !reused as u64, reused as u64, cells, 3 * cells);
+        changed
+    }
+
+    /// Packs up to 64 cells into a word, cell `i` being bit `i`. Eight cells at a time, a
+    /// multiplication gathers the low bits of eight 0 or 1 bytes into the top byte.
+    fn pack_word(cells: &[bool]) -> u64 {
+        let chunks = cells.chunks_exact(8);
+        let remainder = chunks.remainder();
+        let mut word = 0;
+        for (index, chunk) in chunks.enumerate() {
+            let bytes: [u8; 8] = std::array::from_fn(|bit| chunk[bit] as u8);
+            word |= (u64::from_le_bytes(bytes).wrapping_mul(0x0102_0408_1020_4080) >> 56) << (8 * index);
+        }
+        for (bit, &cell) in remainder.iter().enumerate() {
+            word |= (cell as u64) << (cells.len() - remainder.len() + bit);
+        }
+        word
+    }
+
+    /// Unpacks the low `cells.len()` bits of `word` into `cells`, the inverse of
+    /// `pack_word`, looking up eight cells at a time.
+    fn unpack_word(word: u64, cells: &mut [bool]) {
+        let offset = cells.len() / 8 * 8;
+        let mut chunks = cells.chunks_exact_mut(8);
+        for (index, chunk) in chunks.by_ref().enumerate() {
+            let spread = SPREAD_BITS[(word >> (8 * index) & 0xff) as usize];
+            let bits: [bool; 8] = std::array::from_fn(|bit| spread >> (8 * bit) & 1 != 0);
+            chunk.copy_from_slice(&bits);
+        }
+        for (bit, cell) in chunks.into_remainder().iter_mut().enumerate() {
+            *cell = word >> (offset + bit) & 1 != 0;
+        }
+    }
+
+    /// Fills `west` and `east` with the packed `row` shifted so that each cell's bit holds
+    /// its west or east neighbor, wrapping around the row's `width` cells if `toroidal`.
+    fn shift_packed_row(row: &[u64], width: usize, toroidal: bool, west: &mut [u64], east: &mut [u64]) {
+        let words = row.len();
+        let last_bit = (width - 1) % 64;
+        let first_cell = row[0] & 1;
+        let last_cell = row[words - 1] >> last_bit & 1;
+        for i in 0..words {
+            // West neighbor of cell x is cell x - 1: shift towards higher bits
+            let carry_in = if i > 0 { row[i - 1] >> 63 } else if toroidal { last_cell } else { 0 };
+            west[i] = row[i] << 1 | carry_in;
+            // East neighbor of cell x is cell x + 1: shift towards lower bits
+            let carry_in = if i + 1 < words { row[i + 1] << 63 } else { 0 };
+            east[i] = row[i] >> 1 | carry_in;
+        }
+        if toroidal {
+            east[words - 1] |= first_cell << last_bit;
+        }
+    }
+
+    /// Applies the Life rule like `next_naive`, then XORs the result with the generation
+    /// before the current one. A universe that was never ticked has an all-dead one.
+    fn next_second_order(&mut self) -> bool {
+        let before = std::mem::take(&mut self.previous_cells);
+        self.next_naive();
+        for (cell, &was_alive) in self.cells.iter_mut().zip(&before) {
+            *cell ^= was_alive;
+        }
+        self.cells != self.previous_cells
+    }
+
+    fn next_naive_per_cell(&mut self) -> bool {
+        let (mut next, reused) = self.take_back_buffer();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let idx = (y * self.width + x) as usize;
+                next[idx] = self.next_array_cell_state(x, y);
+            }
+        }
+        let changed = next != self.cells;
+        self.previous_cells = std::mem::replace(&mut self.cells, next);
+        self.record_per_cell_profile(reused);
+        changed
+    }
+
+    fn next_hashset(&mut self) -> bool {
+        let mut next = CellSet::default();
+        let mut to_check = CellSet::default();
+
+        // Populate to_check with all cells that are alive and their neighbors
+        for &(x, y) in self.cells_hashset.iter() {
+            for delta_y in -1..=1 {
+                for delta_x in -1..=1 {
+                    if delta_y == 0 && delta_x == 0 {
+                        continue;
+                    }
+
+                    to_check.insert(self.wrap(x as i64 + delta_x, y as i64 + delta_y));
+                }
+            }
+            to_check.insert((x, y)); // Include the cell itself to be checked
+        }
+
+        // Check each cell in to_check to determine if it should be alive in the next state
+        let visited = to_check.len() as u64;
+        for (x, y) in to_check {
+            let cell_alive = self.cells_hashset.contains(&(x, y));
+            let next_state = match self.isotropic_rule.as_ref().filter(|_| self.uses_neighbor_masks()) {
+                Some(rule) => rule.next_state(cell_alive, self.neighbor_mask(x, y, |neighbor_x, neighbor_y| {
+                    self.cells_hashset.contains(&(neighbor_x, neighbor_y))
+                })),
+                None => self.next_cell_state(cell_alive, self.live_neighbor_count_hashset(x, y)),
+            };
+            if next_state {
+                next.insert((x, y));
+            }
+        }
+
+        let changed = next != self.cells_hashset;
+        self.previous_cells_hashset = Some(std::mem::replace(&mut self.cells_hashset, next));
+        self.record_profile(2, 0, visited, 8 * visited);
+        changed
+    }
+
+    fn next_parallel(&mut self) -> bool {
+        if !self.uses_row_counts() || self.uses_neighbor_masks() {
+            return self.next_parallel_per_cell();
+        }
+
+        let width = self.width as usize;
+        let (mut next, reused) = self.take_back_buffer();
+        let scratch_buffers = AtomicU64::new(0);
+        next.par_chunks_mut(width).enumerate().for_each_init(
+            || {
+                scratch_buffers.fetch_add(2, Ordering::Relaxed);
+                (vec![0; width], vec![0; width])
+            },
+            |(sums, counts), (y, next_row)| {
+                self.row_neighbor_counts(y, sums, counts);
+                self.next_row_states(y, counts, next_row);
+            },
+        );
+        let changed = next != self.cells;
+        self.previous_cells = std::mem::replace(&mut self.cells, next);
+        let cells = self.cells.len() as u64;
+        self.record_profile(!reused as u64 + scratch_buffers.into_inner(), reused as u64, cells, 3 * cells);
+        changed
+    }
+
+    fn next_parallel_per_cell(&mut self) -> bool {
+        let width = self.width;
+        let (mut next, reused) = self.take_back_buffer();
+        next.par_iter_mut()
+            .enumerate()
+            .for_each(|(i, cell)| *cell = self.next_array_cell_state(i as u32 % width, i as u32 / width));
+        let changed = next != self.cells;
+        self.previous_cells = std::mem::replace(&mut self.cells, next);
+        self.record_per_cell_profile(reused);
+        changed
+    }
+
+    /// Whether the next state depends on the neighbor mask, which the isotropic rule needs.
+    /// A custom rule function still takes precedence.
+    fn uses_neighbor_masks(&self) -> bool {
+        self.isotropic_rule.is_some() && self.rule_fn.is_none()
+    }
+
+    fn next_array_cell_state(&self, x: u32, y: u32) -> bool {
+        let cell = self.cells[(y * self.width + x) as usize];
+        match self.isotropic_rule.as_ref().filter(|_| self.uses_neighbor_masks()) {
+            Some(rule) => rule.next_state(cell, self.neighbor_mask(x, y, |neighbor_x, neighbor_y| {
+                self.cells[(neighbor_y * self.width + neighbor_x) as usize]
+            })),
+            None => self.next_cell_state(cell, self.live_neighbor_count_array(x, y)),
+        }
+    }
+
+    /// Which of the 8 neighbors are alive, as a mask in the bit order of `HENSEL_MASKS`.
+    /// Honors the boundary mode, on a torus thinner than 3 cells a neighbor may show up
+    /// under several bits.
+    fn neighbor_mask(&self, x: u32, y: u32, is_alive: impl Fn(u32, u32) -> bool) -> u8 {
+        let (width, height) = (self.width as i64, self.height as i64);
+        let mut mask = 0;
+        let mut bit = 0;
+        for delta_y in -1..=1 {
+            for delta_x in -1..=1 {
+                if delta_y == 0 && delta_x == 0 {
+                    continue;
+                }
+
+                let (neighbor_x, neighbor_y) = (x as i64 + delta_x, y as i64 + delta_y);
+                let alive = match self.boundary {
+                    BoundaryMode::Toroidal => {
+                        let (neighbor_x, neighbor_y) = self.wrap(neighbor_x, neighbor_y);
+                        is_alive(neighbor_x, neighbor_y)
+                    }
+                    BoundaryMode::Dead => {
+                        (0..width).contains(&neighbor_x) && (0..height).contains(&neighbor_y) && is_alive(neighbor_x as u32, neighbor_y as u32)
+                    }
+                };
+                if alive {
+                    mask |= 1 << bit;
+                }
+                bit += 1;
+            }
+        }
+        mask
+    }
+
+    fn live_neighbor_count_array(&self, x: u32, y: u32) -> NeighborCount {
+        if let Some(table) = self.neighbor_table.as_ref().filter(|_| self.boundary == BoundaryMode::Toroidal && self.shear == 0) {
+            let neighbors = &table[(y * self.width + x) as usize];
+            return neighbors.iter().filter(|&&idx| self.cells[idx]).count() as NeighborCount;
+        }
+        self.live_neighbor_count(x, y, |neighbor_x, neighbor_y| self.cells[(neighbor_y * self.width + neighbor_x) as usize])
+    }
+
+    fn live_neighbor_count_hashset(&self, x: u32, y: u32) -> NeighborCount {
+        self.live_neighbor_count(x, y, |neighbor_x, neighbor_y| self.cells_hashset.contains(&(neighbor_x, neighbor_y)))
+    }
+
+    /// Counts the live neighbors of `(x, y)` under the boundary mode, the one routine every
+    /// implementation's per-cell counting goes through, so they agree on the edges.
+    /// `(x, y)` must lie on the grid, `is_alive` is only asked about cells on it.
+    fn live_neighbor_count(&self, x: u32, y: u32, is_alive: impl Fn(u32, u32) -> bool) -> NeighborCount {
+        match self.boundary {
+            BoundaryMode::Toroidal => self.live_neighbor_count_wrapped(x, y, is_alive),
+            BoundaryMode::Dead => self.live_neighbor_count_bounded(x, y, is_alive),
+        }
+    }
+
+    /// Whether the torus is twisted by `set_shear`.
+    fn is_sheared(&self) -> bool {
+        self.boundary == BoundaryMode::Toroidal && self.shear != 0
+    }
+
+    /// Wraps the position `(x, y)`, which may lie off the grid, around the torus. Every
+    /// crossing of the east edge moves it `shear` rows south, of the west edge north.
+    fn wrap(&self, x: i64, y: i64) -> (u32, u32) {
+        let (width, height) = (self.width as i64, self.height as i64);
+        let y = y + x.div_euclid(width) * self.shear as i64;
+        (x.rem_euclid(width) as u32, y.rem_euclid(height) as u32)
+    }
+
+    /// Counts live neighbors with the edges wrapping around. On a grid thinner than 3
+    /// cells a neighbor reached several ways counts that many times, unless
+    /// `dedup_neighbors` is set.
+    fn live_neighbor_count_wrapped(&self, x: u32, y: u32, is_alive: impl Fn(u32, u32) -> bool) -> NeighborCount {
+        if self.dedup_neighbors && (self.width < 3 || self.height < 3) {
+            return self.live_neighbor_count_distinct(x, y, is_alive);
+        }
+        if self.is_sheared() {
+            // Which way a neighbor crosses the east or west edge decides the shear
+            let mut count = 0;
+            for delta_y in -1..=1 {
+                for delta_x in -1..=1 {
+                    let (neighbor_x, neighbor_y) = self.wrap(x as i64 + delta_x, y as i64 + delta_y);
+                    if (delta_x, delta_y) != (0, 0) && is_alive(neighbor_x, neighbor_y) {
+                        count += 1;
+                    }
+                }
+            }
+            return count;
+        }
+
+        let mut count = 0;
+        for delta_y in [self.height.wrapping_sub(1), 0, 1] {
+            for delta_x in [self.width.wrapping_sub(1), 0, 1] {
+                if (delta_x, delta_y) != (0, 0) && is_alive((x + delta_x) % self.width, (y + delta_y) % self.height) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Counts each distinct wrapped neighbor once, never counting the cell itself.
+    fn live_neighbor_count_distinct(&self, x: u32, y: u32, is_alive: impl Fn(u32, u32) -> bool) -> NeighborCount {
+        let mut neighbors: Vec<(u32, u32)> = Vec::with_capacity(8);
+        for delta_y in -1..=1 {
+            for delta_x in -1..=1 {
+                let neighbor = self.wrap(x as i64 + delta_x, y as i64 + delta_y);
+                if neighbor != (x, y) && !neighbors.contains(&neighbor) {
+                    neighbors.push(neighbor);
+                }
+            }
+        }
+        neighbors.into_iter().filter(|&(neighbor_x, neighbor_y)| is_alive(neighbor_x, neighbor_y)).count() as NeighborCount
+    }
+
+    /// Counts live neighbors without wrapping, cells beyond the edges count as dead.
+    fn live_neighbor_count_bounded(&self, x: u32, y: u32, is_alive: impl Fn(u32, u32) -> bool) -> NeighborCount {
+        let mut count = 0;
+        for neighbor_y in y.saturating_sub(1)..=(y + 1).min(self.height - 1) {
+            for neighbor_x in x.saturating_sub(1)..=(x + 1).min(self.width - 1) {
+                if (neighbor_x, neighbor_y) != (x, y) && is_alive(neighbor_x, neighbor_y) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+}
+
+/// The part of the API exported to JavaScript under the `wasm` feature, what a renderer
+/// needs. Methods of the Rust API are wrapped, a type can not have two methods of the same
+/// name, and exported under their Rust names with `js_name`.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Universe {
+    /// `new Universe(width, height)` in JavaScript, a random universe, see `new_default`.
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(width: u32, height: u32) -> Universe {
+        Universe::new_default(width, height)
+    }
+
+    #[wasm_bindgen(js_name = tick)]
+    pub fn tick_js(&mut self) {
+        self.tick();
+    }
+
+    #[wasm_bindgen(js_name = reset)]
+    pub fn reset_js(&mut self) {
+        self.reset();
+    }
+
+    /// Ticks once and reports whether the pattern settled, see `tick_checked`.
+    #[wasm_bindgen(js_name = tick_checked)]
+    pub fn tick_checked_js(&mut self) -> u8 {
+        self.tick_checked()
+    }
+
+    /// Jumps ahead `generations` generations, see `fast_forward`.
+    #[wasm_bindgen(js_name = fast_forward)]
+    pub fn fast_forward_js(&mut self, generations: u32) {
+        self.fast_forward(generations);
+    }
+
+    #[wasm_bindgen(js_name = set_running)]
+    pub fn set_running_js(&mut self, running: bool) {
+        self.set_running(running);
+    }
+
+    #[wasm_bindgen(js_name = is_running)]
+    pub fn is_running_js(&self) -> bool {
+        self.running
+    }
+
+    #[wasm_bindgen(js_name = set_tps)]
+    pub fn set_tps_js(&mut self, tps: f64) {
+        self.set_tps(tps);
+    }
+
+    /// Advances the animation by the time since the last frame, see `update`.
+    #[wasm_bindgen(js_name = update)]
+    pub fn update_js(&mut self, dt_ms: f64) -> u32 {
+        self.update(dt_ms)
+    }
+
+    #[wasm_bindgen(js_name = rule_string)]
+    pub fn rule_string_js(&self) -> String {
+        self.rule_string()
+    }
+
+    /// Replaces the rule, see `set_rule_string`. Throws the parse error's message on an
+    /// invalid rulestring, leaving the rule unchanged.
+    #[wasm_bindgen(js_name = set_rule_string)]
+    pub fn set_rule_string_js(&mut self, rulestring: &str) -> Result<(), JsValue> {
+        self.set_rule_string(rulestring).map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Resizes the grid for a new canvas size, see `resize`.
+    #[wasm_bindgen(js_name = set_size)]
+    pub fn set_size_js(&mut self, width: u32, height: u32) {
+        self.set_size(width, height);
+    }
+
+    #[wasm_bindgen(js_name = width)]
+    pub fn width_js(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(js_name = height)]
+    pub fn height_js(&self) -> u32 {
+        self.height
+    }
+
+    /// The cells as a JS array of one byte per cell in row-major order, 1 for alive and 0
+    /// for dead. Larger than `cells_as_bytes`, but indexed directly without unpacking bits
+    /// or reading wasm memory through a pointer.
+    pub fn get_cells_u8(&self) -> js_sys::Uint8Array {
+        let bytes: Vec<u8> = self.live_mask().into_iter().map(u8::from).collect();
+        js_sys::Uint8Array::from(bytes.as_slice())
+    }
+}
+
+
+pub fn write_state_to_file(universe: &Universe, file_path: &str, iterations: u32) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    // Write width, height, number of iterations, rule and boundary mode as the first line
+    writeln!(file, "{} {} {} {} {}", universe.width, universe.height, iterations, universe.rule_string(), universe.boundary)?;
+
+    match universe.implementation {
+        Implementation::HashSet => {
+            for y in 0..universe.height {
+                for x in 0..universe.width {
+                    let symbol = if universe.cells_hashset.contains(&(x, y)) {
+                        'O'
+                    } else {
+                        '.'
+                    };
+                    write!(file, "{}", symbol)?;
+                }
+                writeln!(file)?;
+            }
+        }
+        _ => {
+            for y in 0..universe.height {
+                for x in 0..universe.width {
+                    let idx = (y * universe.width + x) as usize;
+                    let symbol = if universe.cells[idx] {
+                        'O'
+                    } else {
+                        '.'
+                    };
+                    write!(file, "{}", symbol)?;
+                }
+                writeln!(file)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn write_state_to_rle(universe: &Universe, file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+    if let Some(name) = &universe.pattern_name {
+        writeln!(file, "#N {}", name)?;
+    }
+    if let Some(author) = &universe.author {
+        writeln!(file, "#O {}", author)?;
+    }
+    if let Some(comment) = &universe.comment {
+        for line in comment.lines() {
+            writeln!(file, "#C {}", line)?;
+        }
+    }
+    writeln!(file, "x = {}, y = {}, rule = {}", universe.width, universe.height, universe.rule_string())?;
+
+    // Collect (run length, tag) tokens, merging consecutive equal tags
+    let mut tokens: Vec<(u32, char)> = Vec::new();
+    let push = |tokens: &mut Vec<(u32, char)>, tag: char| match tokens.last_mut() {
+        Some((count, last)) if *last == tag => *count += 1,
+        _ => tokens.push((1, tag)),
+    };
+    for y in 0..universe.height {
+        for x in 0..universe.width {
+            push(&mut tokens, if universe.get_cell(y, x) { 'o' } else { 'b' });
+        }
+        // Dead cells at the end of a row are implied
+        if matches!(tokens.last(), Some((_, 'b'))) {
+            tokens.pop();
+        }
+        push(&mut tokens, '$');
+    }
+    if matches!(tokens.last(), Some((_, '$'))) {
+        tokens.pop();
+    }
+    tokens.push((1, '!'));
+
+    // Keep lines at most 70 characters long
+    let mut line = String::new();
+    for (count, tag) in tokens {
+        let token = if count > 1 {
+            format!("{}{}", count, tag)
+        } else {
+            tag.to_string()
+        };
+        if line.len() + token.len() > 70 {
+            writeln!(file, "{}", line)?;
+            line.clear();
+        }
+        line.push_str(&token);
+    }
+    writeln!(file, "{}", line)?;
+
+    Ok(())
+}
+
+pub fn write_state_to_life106(universe: &Universe, file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+    writeln!(file, "#Life 1.06")?;
+
+    for y in 0..universe.height {
+        for x in 0..universe.width {
+            if universe.get_cell(y, x) {
+                writeln!(file, "{} {}", x, y)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the state as a plain PBM (P1) image, live cells are black.
+pub fn write_state_to_image(universe: &Universe, file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+    writeln!(file, "P1")?;
+    writeln!(file, "{} {}", universe.width, universe.height)?;
+
+    for y in 0..universe.height {
+        let row: Vec<&str> = (0..universe.width)
+            .map(|x| if universe.get_cell(y, x) { "1" } else { "0" })
+            .collect();
+        writeln!(file, "{}", row.join(" "))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The buffers are private, so a desync can only be staged from inside the crate
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "cells do not match 4x4")]
+    fn desynced_cells_are_caught() {
+        let mut universe = Universe::empty(4, 4, Implementation::Naive);
+        universe.cells.pop();
+        universe.set_cell(0, 0, true);
+    }
+}
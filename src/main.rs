@@ -1,299 +1,398 @@
-use std::collections::HashSet;
-use std::fs::{self, File};
-use std::io::{self, BufRead, Write};
-use std::time::Instant;
-use rand::Rng;
-use rayon::prelude::*;
 use std::env;
-
-
-#[derive(Debug)]
-pub enum Implementation {
-    Naive,
-    HashSet,
-    Parallel,
-}
-
-pub struct Universe {
-    width: u32,
-    height: u32,
-    cells: Vec<bool>,
-    cells_hashset: HashSet<(u32, u32)>,
-    implementation: Implementation,
+use std::fs;
+use std::io::{self, Read};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use game_of_life::*;
+
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    Txt,
+    Rle,
+    Life106,
+    Pbm,
 }
 
-impl Universe {
-    pub fn new(width: u32, height: u32, implementation: Implementation, seed_path: Option<&String>) -> Universe {
-        let (cells, cells_hashset) = match seed_path {
-            Some(path) => Universe::initialize_from_file(width, height, path),
-            None => Universe::initialize_randomly(width, height),
-        };
-
-        Universe {
-            width,
-            height,
-            cells,
-            cells_hashset,
-            implementation,
+impl OutputFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Txt => "txt",
+            OutputFormat::Rle => "rle",
+            OutputFormat::Life106 => "lif",
+            OutputFormat::Pbm => "pbm",
         }
     }
+}
 
-    fn initialize_randomly(width: u32, height: u32) -> (Vec<bool>, HashSet<(u32, u32)>) {
-        let cells: Vec<bool> = {
-            let mut rng = rand::thread_rng();
-            (0..width * height)
-                .map(|_| rng.gen::<bool>())
-                .collect()
-        };
-        let cells_hashset = Universe::create_hashset(&cells, width, height);
-
-        (cells, cells_hashset)
+/// Removes `<name> <value>` from `args` and returns the value, if the option is present.
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let position = args.iter().position(|arg| arg == name)?;
+    if position + 1 >= args.len() {
+        println!("Missing value for {}", name);
+        std::process::exit(1);
     }
+    args.remove(position);
+    Some(args.remove(position))
+}
 
-    fn initialize_from_file(width: u32, height: u32, path: &str) -> (Vec<bool>, HashSet<(u32, u32)>) {
-        let file = fs::File::open(path).expect("Failed to open seed file");
-        let mut lines = io::BufReader::new(file).lines();
-
-        // First line contains width, height, and iterations
-        let first_line = lines.next().unwrap().expect("Failed to read first line");
-        let dimensions: Vec<u32> = first_line.split_whitespace()
-                                                .map(|number| number.parse().expect("Failed to parse dimensions"))
-                                                .collect();
-
-        if dimensions.len() != 3 || dimensions[0] != width || dimensions[1] != height {
-            panic!("Provided width and height do not match the file's dimensions.");
-        }
-
-        let mut cells = vec![false; (width * height) as usize];
-
-        for (y, line) in lines.enumerate() {
-            let line = line.expect("Could not read line");
-            for (x, char) in line.chars().enumerate() {
-                if x < width as usize && y < height as usize {
-                    cells[y * width as usize + x] = char == 'O';
-                }
-            }
-        }
-
-        let cells_hashset = Universe::create_hashset(&cells, width, height);
-
-        (cells, cells_hashset)
+/// Reads a seed file, or stdin when `path` is `-`.
+fn read_seed(path: &str) -> io::Result<String> {
+    if path == "-" {
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content)?;
+        Ok(content)
+    } else {
+        fs::read_to_string(path)
     }
+}
 
-    fn create_hashset(cells: &Vec<bool>, width: u32, height: u32) -> HashSet<(u32, u32)> {
-        let mut hashset = HashSet::new();
-        for y in 0..height {
-            for x in 0..width {
-                if cells[(y * width + x) as usize] {
-                    hashset.insert((x, y));
-                }
-            }
+/// Removes the boolean flag `name` from `args`, returning whether it was present.
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    match args.iter().position(|arg| arg == name) {
+        Some(position) => {
+            args.remove(position);
+            true
         }
-        hashset
+        None => false,
     }
+}
 
-    pub fn game_of_life(&mut self, iterations: u32) {
-        let start = Instant::now();
-        let next_fn: Box<dyn Fn(&mut Universe)> = match self.implementation {
-            Implementation::Naive => Box::new(|u: &mut Universe| u.next_naive()),
-            Implementation::HashSet => Box::new(|u: &mut Universe| u.next_hashset()),
-            Implementation::Parallel => Box::new(|u: &mut Universe| u.next_parallel()),
-        };
-    
-        for _ in 0..iterations {
-            next_fn(self);
-            
-        }
-    
-        let duration = start.elapsed();
-        println!("{} iterations took {:?} ms using the {:?} implementation", iterations, duration.as_millis(), self.implementation);
+/// Path of the final state file: `game_of_life_<width>_<height>_<iterations>`, followed by
+/// `_<run_id>` if given, inside `dir` if given, otherwise in the current directory.
+fn output_path(dir: Option<&str>, width: u32, height: u32, iterations: u32, run_id: Option<&str>, extension: &str) -> String {
+    let mut name = format!("game_of_life_{}_{}_{}", width, height, iterations);
+    if let Some(run_id) = run_id {
+        name = format!("{}_{}", name, run_id);
     }
-
-    fn next_naive(&mut self) {
-        let mut next = vec![false; (self.width * self.height) as usize];
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let idx = (y * self.width + x) as usize;
-                let cell = self.cells[idx];
-                let live_neighbors = self.live_neighbor_count_array(x, y);
-
-                next[idx] = matches!(
-                    (cell, live_neighbors),
-                    (true, 2) | (true, 3) | (false, 3)
-                );
-            }
-        }
-        self.cells = next;
-        println!();
+    match dir {
+        Some(dir) => format!("{}/{}.{}", dir.trim_end_matches('/'), name, extension),
+        None => format!("{}.{}", name, extension),
     }
+}
 
-    fn next_hashset(&mut self) {
-        let mut next = HashSet::new();
-        let mut to_check: HashSet<(u32, u32)> = HashSet::new();
-
-        // Populate to_check with all cells that are alive and their neighbors
-        for &(x, y) in self.cells_hashset.iter() {
-            for delta_y in [self.height - 1, 0, 1].iter().cloned() {
-                for delta_x in [self.width - 1, 0, 1].iter().cloned() {
-                    if delta_y == 0 && delta_x == 0 {
-                        continue;
-                    }
-
-                    let neighbor_x = (x + delta_x) % self.width;
-                    let neighbor_y = (y + delta_y) % self.height;
-                    to_check.insert((neighbor_x, neighbor_y));
-                }
-            }
-            to_check.insert((x, y)); // Include the cell itself to be checked
-        }
-
-        // Check each cell in to_check to determine if it should be alive in the next state
-        for (x, y) in to_check {
-            let live_neighbors = self.live_neighbor_count_hashset(x, y);
-            let cell_alive = self.cells_hashset.contains(&(x, y));
-            if (cell_alive && (live_neighbors == 2 || live_neighbors == 3)) || (!cell_alive && live_neighbors == 3) {
-                next.insert((x, y));
-            }
-        }
+/// Most generations the `stable` iteration mode runs looking for the pattern to settle.
+const STABLE_GENERATION_LIMIT: u32 = 10_000;
 
-        self.cells_hashset = next;
+/// Describes how a pattern settled, for the `stable` iteration mode.
+fn describe_stability(stability: Stability) -> String {
+    match stability {
+        Stability::StillLife => "still life".to_string(),
+        Stability::Oscillator { period } => format!("oscillator with period {}", period),
+        Stability::Spaceship { period, dx, dy } => format!("spaceship with period {} moving by ({}, {})", period, dx, dy),
+        Stability::Unsettled => "not settled".to_string(),
     }
+}
 
-    fn next_parallel(&mut self) {
-        let cells = &self.cells;
-        let width = self.width;
-        let height = self.height;
-
-        self.cells = (0..height * width)
-            .into_par_iter()
-            .map(|i| {
-                let x = (i % width) as u32;
-                let y = (i / width) as u32;
-                let cell = cells[i as usize];
-                let live_neighbors = self.live_neighbor_count_array(x, y);
+/// Loads a seed or saved state file sized by its own header, returning its content and
+/// the universe. Exits with status 1 if it can not be read or parsed.
+fn load_seed(path: &str, strict: bool) -> (String, Universe) {
+    let content = read_seed(path).unwrap_or_else(|error| {
+        println!("Failed to read seed file {}: {}", path, error);
+        std::process::exit(1);
+    });
+    let universe = Universe::parse_seed_header(&content)
+        .and_then(|header| Universe::from_seed_str(header.width, header.height, Implementation::Naive, &content, strict))
+        .unwrap_or_else(|error| {
+            println!("Invalid seed file {}: {}", path, error);
+            std::process::exit(1);
+        });
+    (content, universe)
+}
 
-                match (cell, live_neighbors) {
-                    (true, x) if x < 2 => false,
-                    (true, 2) | (true, 3) => true,
-                    (true, x) if x > 3 => false,
-                    (false, 3) => true,
-                    (otherwise, _) => otherwise,
-                }
-            })
-            .collect();
+/// Parses a seed file strictly, without simulating, and prints a summary of it. Exits with
+/// status 1 if it can not be read or does not fit its header. Missing rows are allowed,
+/// they are dead, but pointed out since they may mean a truncated file.
+fn validate_seed(path: &str) {
+    let (content, universe) = load_seed(path, true);
+    println!(
+        "{}: {}x{}, {} live cells, generation {}, {} {}",
+        path,
+        universe.width(),
+        universe.height(),
+        universe.population(),
+        universe.generation(),
+        universe.rule_string(),
+        universe.boundary_mode()
+    );
+    let rows = content.lines().skip(1).count() as u32;
+    if rows < universe.height() {
+        println!("Only {} of {} rows present, the rest are dead.", rows, universe.height());
     }
+}
 
-    fn live_neighbor_count_array(&self, x: u32, y: u32) -> u8 {
-        let mut count = 0;
-        for delta_y in [self.height.wrapping_sub(1), 0, 1] {
-            for delta_x in [self.width.wrapping_sub(1), 0, 1] {
-                if delta_y == 0 && delta_x == 0 {
-                    continue;
-                }
-    
-                let neighbor_x = (x + delta_x) % self.width;
-                let neighbor_y = (y + delta_y) % self.height;
-                let idx = (neighbor_y * self.width + neighbor_x) as usize;
-                if self.cells[idx] {
-                    count += 1;
-                }
-            }
+/// Compares the grids of two saved state files, printing whether they are identical and
+/// otherwise how many cells differ and the first one in row-major order. Exits with
+/// status 1 if they differ, including in their dimensions.
+fn compare_states(first_path: &str, second_path: &str) {
+    let (_, first) = load_seed(first_path, false);
+    let (_, second) = load_seed(second_path, false);
+    let differences = first.diff(&second).unwrap_or_else(|error| {
+        println!("{} and {} differ in size: {}", first_path, second_path, error);
+        std::process::exit(1);
+    });
+    match differences.first() {
+        None => println!("{} and {} are identical", first_path, second_path),
+        Some((row, col)) => {
+            println!("{} cell(s) differ, the first at row {}, column {}", differences.len(), row, col);
+            std::process::exit(1);
         }
-        count
     }
+}
 
-    fn live_neighbor_count_hashset(&self, x: u32, y: u32) -> u8 {
-        let mut count = 0;
-        for delta_y in [self.height.wrapping_sub(1), 0, 1].iter().cloned() {
-            for delta_x in [self.width.wrapping_sub(1), 0, 1].iter().cloned() {
-                if delta_y == 0 && delta_x == 0 {
-                    continue;
-                }
-    
-                let neighbor_x = (x + delta_x) % self.width;
-                let neighbor_y = (y + delta_y) % self.height;
-                if self.cells_hashset.contains(&(neighbor_x, neighbor_y)) {
-                    count += 1;
-                }
+/// Grid sizes of `--profile-implementations` when `--sizes` is not given.
+const DEFAULT_PROFILE_SIZES: [u32; 4] = [64, 128, 256, 512];
+
+/// Runs every implementation on square grids of each size from the same random seed and
+/// prints one CSV row per (size, implementation) pair.
+fn profile_implementations(sizes: &[u32], iterations: u32, seed: u64) {
+    println!("implementation,width,height,iterations,total_ms,ms_per_gen");
+    for &size in sizes {
+        for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Parallel, Implementation::Simd] {
+            let name = implementation_to_str(implementation);
+            let mut universe = Universe::new_with_seed(size, size, implementation, seed);
+            let start = Instant::now();
+            for _ in 0..iterations {
+                universe.tick();
             }
+            let total_ms = start.elapsed().as_secs_f64() * 1000.0;
+            let ms_per_gen = if iterations == 0 { 0.0 } else { total_ms / iterations as f64 };
+            println!("{},{},{},{},{:.3},{:.3}", name, size, size, iterations, total_ms, ms_per_gen);
         }
-        count
     }
 }
 
-
-fn write_state_to_file(universe: &Universe, file_path: &str, iterations: u32) -> io::Result<()> {
-    let mut file = File::create(file_path)?;
-
-    // Write width, height, and number of iterations as the first line
-    writeln!(file, "{} {} {}", universe.width, universe.height, iterations)?;
-
-    match universe.implementation {
-        Implementation::HashSet => {
-            for y in 0..universe.height {
-                for x in 0..universe.width {
-                    let symbol = if universe.cells_hashset.contains(&(x, y)) {
-                        'O'
-                    } else {
-                        '.'
-                    };
-                    write!(file, "{}", symbol)?;
-                }
-                writeln!(file)?;
-            }
-        }
-        _ => {
-            for y in 0..universe.height {
-                for x in 0..universe.width {
-                    let idx = (y * universe.width + x) as usize;
-                    let symbol = if universe.cells[idx] {
-                        'O'
-                    } else {
-                        '.'
-                    };
-                    write!(file, "{}", symbol)?;
-                }
-                writeln!(file)?;
-            }
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+
+    let strict = take_flag(&mut args, "--strict");
+    let auto_size = take_flag(&mut args, "--auto-size");
+    let profile = take_flag(&mut args, "--profile-implementations");
+    let sizes = take_option(&mut args, "--sizes").map(|sizes| {
+        sizes.split(',').map(|size| size.trim().parse::<u32>()).collect::<Result<Vec<u32>, _>>().unwrap_or_else(|_| {
+            println!("Invalid --sizes '{}'. Expected comma separated grid sizes, e.g. 64,128,256.", sizes);
+            std::process::exit(1);
+        })
+    });
+    let boundary = take_option(&mut args, "--boundary").map(|boundary| {
+        boundary.parse::<BoundaryMode>().unwrap_or_else(|_| {
+            println!("Invalid boundary. Choose from 'toroidal' or 'dead'.");
+            std::process::exit(1);
+        })
+    });
+    let rule = take_option(&mut args, "--rule");
+    let random_seed = take_option(&mut args, "--seed").map(|seed| {
+        seed.parse::<u64>().unwrap_or_else(|_| {
+            println!("Invalid seed '{}'. Expected an unsigned 64-bit integer.", seed);
+            std::process::exit(1);
+        })
+    });
+    let frames_dir = take_option(&mut args, "--frames");
+    let output_dir = take_option(&mut args, "--output-dir");
+    let timestamp = take_flag(&mut args, "--timestamp");
+    let mut run_id = take_option(&mut args, "--run-id");
+    if timestamp {
+        if run_id.is_some() {
+            println!("--run-id and --timestamp cannot be used together.");
+            std::process::exit(1);
         }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("System clock is before 1970");
+        run_id = Some(now.as_secs().to_string());
     }
+    let max_memory = match take_option(&mut args, "--max-memory") {
+        Some(bytes) => bytes.parse::<u64>().unwrap_or_else(|_| {
+            println!("Invalid --max-memory '{}'. Expected a number of bytes.", bytes);
+            std::process::exit(1);
+        }),
+        None => DEFAULT_MAX_MEMORY,
+    };
+    let pattern = take_option(&mut args, "--pattern").map(|name| {
+        Pattern::named(&name).unwrap_or_else(|| {
+            println!("Unknown pattern '{}'. Choose from: {}.", name, PATTERN_NAMES.join(", "));
+            std::process::exit(1);
+        })
+    });
+
+    let format = match take_option(&mut args, "--format").as_deref() {
+        None | Some("txt") => OutputFormat::Txt,
+        Some("rle") => OutputFormat::Rle,
+        Some("life106") => OutputFormat::Life106,
+        Some("pbm") => OutputFormat::Pbm,
+        Some(_) => {
+            println!("Invalid format. Choose from 'txt', 'rle', 'life106', or 'pbm'.");
+            std::process::exit(1);
+        }
+    };
 
-    Ok(())
-}
+    if args.get(1).is_some_and(|command| command == "validate") {
+        let Some(path) = args.get(2) else {
+            println!("Usage: game_of_life validate <seed_file>");
+            std::process::exit(1);
+        };
+        validate_seed(path);
+        return;
+    }
+    if args.get(1).is_some_and(|command| command == "compare") {
+        let (Some(first_path), Some(second_path)) = (args.get(2), args.get(3)) else {
+            println!("Usage: game_of_life compare <state_file> <state_file>");
+            std::process::exit(1);
+        };
+        compare_states(first_path, second_path);
+        return;
+    }
 
+    if profile {
+        let Some(iterations) = args.get(1).and_then(|iterations| iterations.parse::<u32>().ok()) else {
+            println!("Usage: game_of_life --profile-implementations <iterations> [--sizes <n,n,...>] [--seed <u64>]");
+            std::process::exit(1);
+        };
+        let sizes = sizes.unwrap_or_else(|| DEFAULT_PROFILE_SIZES.to_vec());
+        profile_implementations(&sizes, iterations, random_seed.unwrap_or(0));
+        return;
+    }
 
+    // The seed file comes right after <iterations> <implementation>, `-` reads it from stdin
+    let seed_path = args.get(if auto_size { 3 } else { 5 }).cloned();
+    let seed = seed_path.as_deref().map(|path| {
+        read_seed(path).unwrap_or_else(|error| {
+            println!("Failed to read seed file {}: {}", path, error);
+            std::process::exit(1);
+        })
+    });
+    let seed_header = |seed: &str| {
+        Universe::parse_seed_header(seed).unwrap_or_else(|error| {
+            println!("Invalid seed file {}: {}", seed_path.as_deref().unwrap_or_default(), error);
+            std::process::exit(1);
+        })
+    };
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+    if auto_size {
+        // The seed's header supplies the dimensions that are otherwise passed first
+        let Some(seed) = &seed else {
+            println!("Usage: game_of_life --auto-size <iterations> <implementation> <seed_file> [options]");
+            std::process::exit(1);
+        };
+        let header = seed_header(seed);
+        args.splice(1..1, [header.width.to_string(), header.height.to_string()]);
+    }
 
     if args.len() < 5 {
-        println!("Usage: game_of_life <width> <height> <iterations> <implementation> [seed_file (random if empty)]");
+        println!("Usage: game_of_life <width> <height> <iterations|stable> <implementation> [seed_file (random if empty)] [--format txt|rle|life106|pbm] [--strict] [--pattern <name>] [--boundary toroidal|dead] [--rule <rulestring>] [--seed <u64>] [--frames <dir>] [--max-memory <bytes>] [--auto-size] [--output-dir <dir>] [--run-id <id> | --timestamp]");
+        std::process::exit(1);
+    }
+
+    if pattern.is_some() && args.len() > 5 {
+        println!("A seed file and --pattern cannot be used together.");
+        std::process::exit(1);
+    }
+    if random_seed.is_some() && (pattern.is_some() || args.len() > 5) {
+        println!("--seed only applies to random initialization, not to a seed file or --pattern.");
         std::process::exit(1);
     }
 
     let width = args[1].parse::<u32>().unwrap();
     let height = args[2].parse::<u32>().unwrap();
-    let iterations = args[3].parse::<u32>().unwrap();
-    let implementation = match args[4].as_str() {
-        "naive" => Implementation::Naive,
-        "hash" => Implementation::HashSet,
-        "parallel" => Implementation::Parallel,
-        _ => {
-            println!("Invalid implementation. Choose from 'naive', 'hash', or 'parallel'.");
-            std::process::exit(1);
+    // `stable` runs until the pattern settles instead of a fixed number of iterations
+    let stable = args[3] == "stable";
+    if stable && frames_dir.is_some() {
+        println!("--frames can not be used with the stable iteration mode.");
+        std::process::exit(1);
+    }
+    let iterations = if stable { 0 } else { args[3].parse::<u32>().unwrap() };
+    let implementation = implementation_from_str(&args[4]).unwrap_or_else(|| {
+        println!("Invalid implementation. Choose from 'naive', 'hash', 'parallel', 'second-order', or 'simd'.");
+        std::process::exit(1);
+    });
+
+    let estimated_memory = Universe::estimated_memory(width, height, &implementation);
+    if estimated_memory > max_memory {
+        println!(
+            "A {}x{} universe needs an estimated {} bytes, more than --max-memory {} bytes.",
+            width, height, estimated_memory, max_memory
+        );
+        std::process::exit(1);
+    }
+    if let Implementation::HashSet = implementation {
+        if width as u64 * height as u64 * HASHSET_BYTES_PER_LIVE_CELL > max_memory {
+            eprintln!(
+                "Warning: the HashSet implementation grows with the live population, a dense {}x{} universe may exceed --max-memory {} bytes.",
+                width, height, max_memory
+            );
         }
-    };
+    }
 
-    let mut total_iterations = iterations;
-    if let Some(seed_path) = args.get(5) {
-        let seed_file_content = fs::read_to_string(seed_path).unwrap();
-        let first_line = seed_file_content.lines().next().unwrap();
-        let previous_iterations: u32 = first_line.split_whitespace().nth(2).unwrap().parse().unwrap();
-        total_iterations += previous_iterations;
+    let mut universe = match &seed {
+        Some(seed) => Universe::from_seed_str(width, height, implementation, seed, strict)
+            .unwrap_or_else(|error| {
+                println!("Invalid seed file {}: {}", seed_path.as_deref().unwrap_or_default(), error);
+                std::process::exit(1);
+            }),
+        None => match pattern {
+            Some(pattern) => {
+                let mut universe = Universe::empty(width, height, implementation);
+                let row = height.saturating_sub(pattern.height()) / 2;
+                let col = width.saturating_sub(pattern.width()) / 2;
+                universe.insert_pattern(&pattern, row, col);
+                universe
+            }
+            None => match random_seed {
+                Some(seed) => Universe::new_with_seed(width, height, implementation, seed),
+                None => Universe::new(width, height, implementation, None),
+            },
+        },
+    };
+    // A seed file's header may set the boundary mode and rule, --boundary and --rule override them
+    if let Some(boundary) = boundary {
+        universe.set_boundary_mode(boundary);
+    }
+    if let Some(rule) = rule {
+        universe.set_rule_string(&rule).unwrap_or_else(|error| {
+            println!("Invalid rule '{}': {}", rule, error);
+            std::process::exit(1);
+        });
     }
+    match frames_dir {
+        Some(dir) => {
+            fs::create_dir_all(&dir).expect("Failed to create frames directory");
+            let mut frame = 0;
+            universe.game_of_life_with(iterations, |universe| {
+                let frame_path = format!("{}/frame_{:04}.pbm", dir, frame);
+                write_state_to_image(universe, &frame_path).expect("Failed to write frame");
+                frame += 1;
+            });
+        }
+        None if stable => {
+            let start = universe.generation();
+            let stability = universe.run_until_stable(STABLE_GENERATION_LIMIT);
+            println!("After {} generations: {}", universe.generation() - start, describe_stability(stability));
+        }
+        None => {
+            universe.game_of_life(iterations);
+        }
+    }
+    // The generation actually reached, which is short of the requested iterations when the
+    // pattern died out, and continues from a seed file's header
+    let total_iterations = universe.generation() as u32;
+    if let Some(dir) = &output_dir {
+        fs::create_dir_all(dir).expect("Failed to create output directory");
+    }
+    let path = output_path(output_dir.as_deref(), width, height, total_iterations, run_id.as_deref(), format.extension());
+    match format {
+        OutputFormat::Txt => write_state_to_file(&universe, &path, total_iterations),
+        OutputFormat::Rle => write_state_to_rle(&universe, &path),
+        OutputFormat::Life106 => write_state_to_life106(&universe, &path),
+        OutputFormat::Pbm => write_state_to_image(&universe, &path),
+    }
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut universe = Universe::new(width, height, implementation, args.get(5));
-    universe.game_of_life(iterations);
-    let path = format!("game_of_life_{}_{}_{}.txt", width, height, total_iterations);
-    write_state_to_file(&universe, &path, total_iterations).unwrap();
+    #[test]
+    fn output_path_joins_the_dir_and_run_id() {
+        assert_eq!(output_path(None, 5, 4, 10, None, "txt"), "game_of_life_5_4_10.txt");
+        assert_eq!(output_path(None, 5, 4, 10, Some("a"), "rle"), "game_of_life_5_4_10_a.rle");
+        assert_eq!(output_path(Some("out"), 5, 4, 10, None, "pbm"), "out/game_of_life_5_4_10.pbm");
+        assert_eq!(output_path(Some("out/"), 5, 4, 10, Some("1700000000"), "lif"), "out/game_of_life_5_4_10_1700000000.lif");
+    }
 }
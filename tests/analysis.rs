@@ -0,0 +1,195 @@
+use game_of_life::{BoundaryMode, Implementation, Pattern, Stability, Symmetry, Universe};
+
+/// An empty `width` x `height` Naive universe with the named pattern at `(row, col)`.
+fn with_pattern(width: u32, height: u32, name: &str, row: u32, col: u32) -> Universe {
+    let mut universe = Universe::empty(width, height, Implementation::Naive);
+    assert!(universe.insert_named_pattern(name, row, col), "unknown pattern {}", name);
+    universe
+}
+
+#[test]
+fn symmetry_flags_reflections() {
+    let symmetric = Symmetry { horizontal: true, vertical: true, diagonal: true };
+    assert_eq!(with_pattern(8, 8, "block", 2, 3).symmetry(), symmetric);
+    let pulsar = Pattern::from_ascii(
+        "
+        ..OOO...OOO..
+        .............
+        O....O.O....O
+        O....O.O....O
+        O....O.O....O
+        ..OOO...OOO..
+        .............
+        ..OOO...OOO..
+        O....O.O....O
+        O....O.O....O
+        O....O.O....O
+        .............
+        ..OOO...OOO..
+        ",
+    );
+    let mut universe = Universe::empty(20, 20, Implementation::Naive);
+    universe.insert_pattern(&pulsar, 3, 4);
+    assert_eq!(universe.symmetry(), symmetric);
+    assert_eq!(
+        with_pattern(8, 8, "beehive", 2, 2).symmetry(),
+        Symmetry { horizontal: true, vertical: true, diagonal: false }
+    );
+    assert_eq!(
+        with_pattern(8, 8, "glider", 2, 2).symmetry(),
+        Symmetry { horizontal: false, vertical: false, diagonal: false }
+    );
+}
+
+#[test]
+fn run_until_stable_detects_spaceships() {
+    let mut glider = with_pattern(20, 20, "glider", 2, 2);
+    assert_eq!(glider.run_until_stable(100), Stability::Spaceship { period: 4, dx: 1, dy: 1 });
+
+    // The built-in lightweight spaceship flies west
+    let mut lwss = with_pattern(30, 12, "lwss", 4, 20);
+    assert_eq!(lwss.run_until_stable(100), Stability::Spaceship { period: 4, dx: -2, dy: 0 });
+
+    assert_eq!(with_pattern(8, 8, "blinker", 3, 3).run_until_stable(100), Stability::Oscillator { period: 2 });
+    assert_eq!(with_pattern(8, 8, "block", 3, 3).run_until_stable(100), Stability::StillLife);
+}
+
+#[test]
+fn is_still_life_leaves_the_state_alone() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Simd] {
+        let mut block = Universe::empty(8, 8, implementation);
+        block.insert_named_pattern("block", 3, 3);
+        assert!(block.is_still_life());
+
+        let mut blinker = Universe::empty(8, 8, implementation);
+        blinker.insert_named_pattern("blinker", 3, 3);
+        blinker.tick();
+        let cells = blinker.live_cells();
+        assert!(!blinker.is_still_life());
+        assert_eq!(blinker.live_cells(), cells, "{:?}", implementation);
+        assert_eq!(blinker.generation(), 1);
+    }
+}
+
+#[test]
+fn find_period_of_oscillators_and_transients() {
+    assert_eq!(with_pattern(8, 8, "blinker", 3, 3).find_period(10), Some(2));
+    assert_eq!(with_pattern(8, 8, "block", 3, 3).find_period(10), Some(1));
+    assert_eq!(with_pattern(8, 8, "beacon", 2, 2).find_period(10), Some(2));
+
+    // On a small torus the R-pentomino's chaos has to end up cycling
+    let period = with_pattern(16, 16, "r-pentomino", 6, 6).find_period(2000);
+    assert!(period.is_some_and(|period| period >= 1));
+
+    // A glider only comes back after crossing the whole 20x20 torus
+    assert_eq!(with_pattern(20, 20, "glider", 2, 2).find_period(50), None);
+    assert_eq!(with_pattern(20, 20, "glider", 2, 2).find_period(100), Some(80));
+}
+
+#[test]
+fn r_pentomino_peaks_and_settles() {
+    // On the infinite plane it peaks at 319 cells in generation 821 and settles in
+    // generation 1103. With dead edges the escaping gliders crash into them instead of
+    // flying off, which shaves a few cells off the peak.
+    let mut universe = Universe::empty(120, 120, Implementation::Naive);
+    universe.set_boundary_mode(BoundaryMode::Dead);
+    universe.insert_named_pattern("r-pentomino", 60, 60);
+    let report = universe.analyze_methuselah(2000);
+    assert!((300..=319).contains(&report.peak_population), "{:?}", report);
+    assert!((800..=840).contains(&report.peak_generation), "{:?}", report);
+    assert!(report.settled_at.is_some_and(|generation| (1050..=1200).contains(&generation)), "{:?}", report);
+    assert!(matches!(report.stability, Stability::StillLife | Stability::Oscillator { period: 2 }));
+    assert_eq!(report.final_population, universe.population());
+}
+
+#[test]
+fn tick_checked_reports_when_the_pattern_settles() {
+    // Three cells of a block, the fourth is born in the first tick
+    let mut block = Universe::empty(8, 8, Implementation::Naive);
+    for (row, col) in [(3, 3), (3, 4), (4, 3)] {
+        block.set_cell(row, col, true);
+    }
+    assert_eq!(block.tick_checked(), 0);
+    assert_eq!(block.tick_checked(), 1);
+    assert_eq!(block.tick_checked(), 1);
+
+    let mut blinker = with_pattern(8, 8, "blinker", 3, 3);
+    assert_eq!(blinker.tick_checked(), 0);
+    assert_eq!(blinker.tick_checked(), 2);
+    // Ticking some other way in between restarts the history
+    blinker.tick();
+    assert_eq!(blinker.tick_checked(), 0);
+}
+
+#[test]
+fn glider_count_ignores_other_objects() {
+    let mut universe = Universe::empty(40, 40, Implementation::Naive);
+    universe.insert_named_pattern("glider", 3, 3);
+    universe.insert_pattern(&Pattern::from_ascii(".O.\nO..\nOOO"), 5, 30);
+    universe.insert_named_pattern("block", 30, 18);
+    assert_eq!(universe.glider_count(), 2);
+
+    // In every phase, until they run into something
+    for _ in 0..12 {
+        universe.tick();
+        assert_eq!(universe.glider_count(), 2, "generation {}", universe.generation());
+    }
+
+    // A glider touching the block is no longer free-flying
+    universe.insert_named_pattern("glider", 27, 17);
+    assert_eq!(universe.glider_count(), 2);
+}
+
+#[test]
+fn objects_are_named_in_any_orientation() {
+    assert_eq!(Pattern::named("beehive").unwrap().classify(), Some("beehive"));
+    assert_eq!(Pattern::named("loaf").unwrap().classify(), Some("loaf"));
+    // A loaf reflected across its diagonal, and a vertical beehive
+    assert_eq!(Pattern::from_ascii(".O..\nO.O.\nO..O\n.OO.").classify(), Some("loaf"));
+    assert_eq!(Pattern::from_ascii(".O.\nO.O\nO.O\n.O.").classify(), Some("beehive"));
+    assert_eq!(Pattern::named("glider").unwrap().classify(), None);
+
+    let mut universe = with_pattern(20, 12, "beehive", 1, 1);
+    universe.insert_named_pattern("loaf", 6, 12);
+    universe.insert_named_pattern("r-pentomino", 1, 14);
+    assert_eq!(universe.classify_objects(), [Some("beehive"), None, Some("loaf")]);
+}
+
+#[test]
+fn disjoint_blocks_are_separate_components() {
+    let mut universe = with_pattern(16, 16, "block", 1, 1);
+    universe.insert_named_pattern("block", 1, 10);
+    universe.insert_named_pattern("block", 9, 5);
+    let components = universe.connected_components(true);
+    assert_eq!(components.len(), 3);
+    assert!(components.iter().all(|component| component.len() == 4));
+    assert_eq!(components[2], [(9, 5), (9, 6), (10, 5), (10, 6)]);
+
+    // Diagonal contact only joins components when diagonals count
+    let mut universe = with_pattern(16, 16, "block", 1, 1);
+    universe.insert_named_pattern("block", 3, 3);
+    assert_eq!(universe.connected_components(true).len(), 1);
+    assert_eq!(universe.connected_components(false).len(), 2);
+
+    // And across the edges of a torus
+    let mut universe = Universe::empty(8, 8, Implementation::Naive);
+    universe.set_cell(3, 0, true);
+    universe.set_cell(3, 7, true);
+    assert_eq!(universe.connected_components(false).len(), 1);
+}
+
+#[test]
+fn edge_activity_counts_the_outer_ring() {
+    let mut universe = Universe::empty(10, 8, Implementation::HashSet);
+    assert_eq!(universe.edge_activity(), 0.0);
+
+    // One cell on each side and a corner, against three inside
+    for (row, col) in [(0, 4), (7, 2), (3, 0), (5, 9), (7, 9), (1, 1), (4, 4), (6, 8)] {
+        universe.set_cell(row, col, true);
+    }
+    assert_eq!(universe.edge_activity(), 5.0 / 8.0);
+
+    universe.set_cell(0, 4, false);
+    universe.set_cell(7, 9, false);
+    assert_eq!(universe.edge_activity(), 3.0 / 6.0);
+}
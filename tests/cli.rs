@@ -0,0 +1,234 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use std::process::{self, Command, Output, Stdio};
+
+/// An empty directory for one test to run the binary in, so its output files do not clash.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("game-of-life-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_game-of-life")).current_dir(dir).args(args).output().unwrap()
+}
+
+#[test]
+fn output_is_named_after_the_generation_reached() {
+    let dir = scratch_dir("died-out");
+    fs::write(dir.join("seed.txt"), "5 5 7\n.....\n..O..\n.....\n.....\n.....\n").unwrap();
+    let output = run(&dir, &["5", "5", "10", "naive", "seed.txt"]);
+    assert!(output.status.success());
+
+    // The single cell dies in the first generation, after the seed's 7
+    let state = fs::read_to_string(dir.join("game_of_life_5_5_8.txt")).unwrap();
+    assert!(state.starts_with("5 5 8 "));
+    assert!(!dir.join("game_of_life_5_5_17.txt").exists());
+}
+
+/// A horizontal blinker in the middle of a 6x6 seed file.
+const BLINKER_SEED: &str = "6 6 0\n......\n......\n.OOO..\n......\n......\n......\n";
+
+#[test]
+fn rle_format_writes_an_rle_file() {
+    let dir = scratch_dir("rle");
+    fs::write(dir.join("seed.txt"), BLINKER_SEED).unwrap();
+    let output = run(&dir, &["6", "6", "1", "naive", "seed.txt", "--format", "rle"]);
+    assert!(output.status.success());
+
+    let state = fs::read_to_string(dir.join("game_of_life_6_6_1.rle")).unwrap();
+    assert_eq!(state.lines().next(), Some("x = 6, y = 6, rule = B3/S23"));
+    assert!(state.trim_end().ends_with('!'));
+    assert!(!dir.join("game_of_life_6_6_1.txt").exists());
+}
+
+/// Number of live cells in a saved `.txt` state, skipping its header.
+fn live_cells(state: &str) -> usize {
+    state.lines().skip(1).map(|line| line.matches('O').count()).sum()
+}
+
+#[test]
+fn pattern_seeds_the_named_pattern() {
+    let dir = scratch_dir("pattern");
+    let output = run(&dir, &["8", "8", "0", "naive", "--pattern", "glider"]);
+    assert!(output.status.success());
+    let state = fs::read_to_string(dir.join("game_of_life_8_8_0.txt")).unwrap();
+    assert_eq!(live_cells(&state), 5);
+
+    let output = run(&dir, &["8", "8", "0", "naive", "--pattern", "no-such-pattern"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("glider"));
+}
+
+#[test]
+fn dead_boundary_changes_a_blinker_on_the_edge() {
+    let dir = scratch_dir("boundary");
+    fs::write(dir.join("seed.txt"), "6 6 0\n......\n......\nO.....\nO.....\nO.....\n......\n").unwrap();
+    let grid = |boundary: &str| {
+        let output = run(&dir, &["6", "6", "1", "naive", "seed.txt", "--boundary", boundary]);
+        assert!(output.status.success());
+        let state = fs::read_to_string(dir.join("game_of_life_6_6_1.txt")).unwrap();
+        state.lines().skip(1).collect::<Vec<_>>().join("\n")
+    };
+
+    // On a torus the blinker turns across the edge, with a dead boundary its outer cell is never born
+    assert_eq!(grid("toroidal"), "......\n......\n......\nOO...O\n......\n......");
+    assert_eq!(grid("dead"), "......\n......\n......\nOO....\n......\n......");
+}
+
+#[test]
+fn same_random_seed_gives_the_same_run() {
+    let dir = scratch_dir("seed");
+    let mut states = Vec::new();
+    for (seed, run_id) in [("7", "first"), ("7", "second"), ("8", "third")] {
+        let output = run(&dir, &["16", "16", "5", "naive", "--seed", seed, "--run-id", run_id]);
+        assert!(output.status.success());
+        states.push(fs::read_to_string(dir.join(format!("game_of_life_16_16_5_{}.txt", run_id))).unwrap());
+    }
+    assert_eq!(states[0], states[1]);
+    assert_ne!(states[0], states[2]);
+}
+
+#[test]
+fn frames_writes_one_pbm_per_generation() {
+    let dir = scratch_dir("frames");
+    let output = run(&dir, &["7", "5", "3", "naive", "--seed", "1", "--frames", "frames"]);
+    assert!(output.status.success());
+
+    let mut frames: Vec<String> = fs::read_dir(dir.join("frames"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    frames.sort();
+    assert_eq!(frames, ["frame_0000.pbm", "frame_0001.pbm", "frame_0002.pbm", "frame_0003.pbm"]);
+    for frame in frames {
+        let image = fs::read_to_string(dir.join("frames").join(frame)).unwrap();
+        assert_eq!(image.lines().take(2).collect::<Vec<_>>(), ["P1", "7 5"]);
+        assert_eq!(image.lines().count(), 2 + 5);
+    }
+}
+
+#[test]
+fn dash_reads_the_seed_from_stdin() {
+    let dir = scratch_dir("stdin");
+    fs::write(dir.join("seed.txt"), BLINKER_SEED).unwrap();
+    let output = run(&dir, &["6", "6", "3", "naive", "seed.txt", "--run-id", "file"]);
+    assert!(output.status.success());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_game-of-life"))
+        .current_dir(&dir)
+        .args(["6", "6", "3", "naive", "-", "--run-id", "stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(BLINKER_SEED.as_bytes()).unwrap();
+    assert!(child.wait().unwrap().success());
+
+    assert_eq!(
+        fs::read_to_string(dir.join("game_of_life_6_6_3_stdin.txt")).unwrap(),
+        fs::read_to_string(dir.join("game_of_life_6_6_3_file.txt")).unwrap()
+    );
+}
+
+#[test]
+fn stable_mode_reports_an_oscillator() {
+    let dir = scratch_dir("stable");
+    fs::write(dir.join("seed.txt"), BLINKER_SEED).unwrap();
+    let output = run(&dir, &["6", "6", "stable", "naive", "seed.txt"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("After 2 generations: oscillator with period 2"));
+    assert!(dir.join("game_of_life_6_6_2.txt").exists());
+}
+
+#[test]
+fn validate_exits_with_the_seed_files_validity() {
+    let dir = scratch_dir("validate");
+    fs::write(dir.join("good.txt"), BLINKER_SEED).unwrap();
+    fs::write(dir.join("bad.txt"), "3 3 0\nOOOO\n").unwrap();
+
+    let output = run(&dir, &["validate", "good.txt"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("6x6, 3 live cells"));
+
+    let output = run(&dir, &["validate", "bad.txt"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("line 2, column 4"));
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 2, "validate must not write a state file");
+}
+
+#[test]
+fn compare_reports_the_first_differing_cell() {
+    let dir = scratch_dir("compare");
+    fs::write(dir.join("state.txt"), BLINKER_SEED).unwrap();
+    fs::write(dir.join("modified.txt"), BLINKER_SEED.replace(".OOO..", ".OO..O")).unwrap();
+
+    let output = run(&dir, &["compare", "state.txt", "state.txt"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("identical"));
+
+    let output = run(&dir, &["compare", "state.txt", "modified.txt"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("2 cell(s) differ, the first at row 2, column 3"));
+}
+
+#[test]
+fn max_memory_rejects_large_universes() {
+    let dir = scratch_dir("max-memory");
+    let output = run(&dir, &["100", "100", "1", "naive", "--max-memory", "19999"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("needs an estimated 20000 bytes"));
+
+    let output = run(&dir, &["100", "100", "1", "naive", "--max-memory", "20000"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn profile_implementations_writes_a_csv_row_per_size_and_implementation() {
+    let dir = scratch_dir("profile");
+    let output = run(&dir, &["--profile-implementations", "3", "--sizes", "16,24", "--seed", "5"]);
+    assert!(output.status.success());
+
+    let csv = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "implementation,width,height,iterations,total_ms,ms_per_gen");
+    assert_eq!(lines.len(), 1 + 2 * 4);
+    assert!(lines[1].starts_with("naive,16,16,3,"));
+    assert!(lines[8].starts_with("simd,24,24,3,"));
+    for line in &lines[1..] {
+        assert_eq!(line.split(',').count(), 6, "{}", line);
+    }
+}
+
+#[test]
+fn output_dir_and_run_id_place_the_output() {
+    let dir = scratch_dir("output-dir");
+    let output = run(&dir, &["6", "6", "2", "naive", "--seed", "1", "--output-dir", "runs/today", "--run-id", "first"]);
+    assert!(output.status.success());
+    assert!(dir.join("runs/today/game_of_life_6_6_2_first.txt").exists());
+
+    let output = run(&dir, &["6", "6", "2", "naive", "--run-id", "first", "--timestamp"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn rule_runs_highlife() {
+    let dir = scratch_dir("rule");
+    fs::write(dir.join("seed.txt"), "7 7 0\n.......\n.......\n..OOO..\n.......\n..OOO..\n.......\n.......\n").unwrap();
+    let output = run(&dir, &["7", "7", "1", "naive", "seed.txt", "--rule", "B36/S23", "--run-id", "highlife"]);
+    assert!(output.status.success());
+    let output = run(&dir, &["7", "7", "1", "naive", "seed.txt", "--run-id", "conway"]);
+    assert!(output.status.success());
+
+    // The cell between the two rows has 6 neighbors, which is a birth in HighLife only
+    let highlife = fs::read_to_string(dir.join("game_of_life_7_7_1_highlife.txt")).unwrap();
+    assert_eq!(highlife, "7 7 1 B36/S23 toroidal\n.......\n...O...\n...O...\n...O...\n...O...\n...O...\n.......\n");
+    let conway = fs::read_to_string(dir.join("game_of_life_7_7_1_conway.txt")).unwrap();
+    assert_eq!(conway, "7 7 1 B3/S23 toroidal\n.......\n...O...\n...O...\n.......\n...O...\n...O...\n.......\n");
+
+    let output = run(&dir, &["7", "7", "1", "naive", "seed.txt", "--rule", "B9/S23"]);
+    assert_eq!(output.status.code(), Some(1));
+}
@@ -0,0 +1,29 @@
+use game_of_life::{Implementation, Universe};
+
+const GLIDER: &str = "
+    .O.
+    ..O
+    OOO
+";
+
+#[test]
+fn glider_moves_one_cell_diagonally_every_four_generations() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Parallel, Implementation::Simd] {
+        let mut universe = Universe::empty(12, 12, implementation);
+        universe.insert_named_pattern("glider", 1, 1);
+        universe.assert_eq_pattern_at(1, 1, GLIDER);
+
+        assert_eq!(universe.run_headless(16), 16);
+        assert_eq!(universe.generation(), 16);
+        universe.assert_eq_pattern_at(5, 5, GLIDER);
+        assert_eq!(universe.live_cells(), vec![(5, 6), (6, 7), (7, 5), (7, 6), (7, 7)]);
+    }
+}
+
+#[test]
+fn glider_wraps_around_the_torus() {
+    let mut universe = Universe::empty(8, 8, Implementation::Naive);
+    universe.insert_named_pattern("glider", 0, 0);
+    universe.run_headless(32);
+    universe.assert_eq_pattern_at(0, 0, GLIDER);
+}
@@ -0,0 +1,343 @@
+use game_of_life::{implementation_from_str, implementation_to_str, BoundaryMode, Implementation, NeighborCount, Universe};
+
+const IMPLEMENTATIONS: [Implementation; 5] = [
+    Implementation::Naive,
+    Implementation::HashSet,
+    Implementation::Parallel,
+    Implementation::SecondOrder,
+    Implementation::Simd,
+];
+
+#[test]
+fn implementation_names_round_trip() {
+    let names = ["naive", "hash", "parallel", "second-order", "simd"];
+    for (implementation, name) in IMPLEMENTATIONS.into_iter().zip(names) {
+        assert_eq!(implementation_to_str(implementation), name);
+        assert_eq!(implementation_from_str(name), Some(implementation));
+    }
+}
+
+#[test]
+fn unknown_implementation_names_are_rejected() {
+    for name in ["", "Naive", "hashset", "second_order", "simd "] {
+        assert_eq!(implementation_from_str(name), None);
+    }
+}
+
+/// Ticks two copies of the same random universe with different implementations after
+/// `configure`, asserting they agree on every generation.
+fn assert_same_evolution(
+    width: u32,
+    height: u32,
+    seed: u64,
+    generations: u32,
+    (expected, actual): (Implementation, Implementation),
+    configure: impl Fn(&mut Universe),
+) {
+    let mut reference = Universe::new_with_seed(width, height, expected, seed);
+    let mut universe = Universe::new_with_seed(width, height, actual, seed);
+    configure(&mut reference);
+    configure(&mut universe);
+    for generation in 1..=generations {
+        reference.tick();
+        universe.tick();
+        assert_eq!(
+            universe.live_cells(),
+            reference.live_cells(),
+            "{:?} differs from {:?} at generation {} of a {}x{} universe from seed {}",
+            actual,
+            expected,
+            generation,
+            width,
+            height,
+            seed
+        );
+    }
+}
+
+#[test]
+fn simd_matches_naive() {
+    let sizes = [(3, 3), (5, 4), (63, 7), (64, 9), (65, 10), (100, 20), (128, 5), (200, 33)];
+    let rules = ["B3/S23", "B36/S23", "B2/S", "B3678/S34678", "B1/S012345678", "B0/S8", "B0123/S", "B3/S23/C4", "B/S"];
+    for (width, height) in sizes {
+        for rule in rules {
+            for boundary in [BoundaryMode::Toroidal, BoundaryMode::Dead] {
+                for seed in 0..2 {
+                    assert_same_evolution(width, height, seed, 12, (Implementation::Naive, Implementation::Simd), |universe| {
+                        universe.set_rule_string(rule).unwrap();
+                        universe.set_boundary_mode(boundary);
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn simd_falls_back_for_what_it_can_not_pack() {
+    let pair = (Implementation::Naive, Implementation::Simd);
+    assert_same_evolution(40, 30, 1, 20, pair, |universe| universe.set_rule_fn(|alive, count| count == 3 || (alive && count == 2)));
+    assert_same_evolution(40, 30, 2, 20, pair, |universe| universe.set_rule_string("B2n3/S23-q").unwrap());
+    assert_same_evolution(40, 30, 3, 20, pair, |universe| universe.set_shear(7));
+    assert_same_evolution(40, 30, 4, 20, pair, |universe| universe.precompute_neighbors(true));
+    assert_same_evolution(2, 9, 5, 20, pair, |_| {});
+}
+
+#[test]
+fn new_default_ticks_like_naive() {
+    let mut universe = Universe::new_default(20, 20);
+    assert_eq!(universe.implementation(), &Implementation::Simd);
+    universe.insert_named_pattern("glider", 0, 0);
+    let mut reference = Universe::empty(20, 20, Implementation::Naive);
+    for (row, col) in universe.live_cells() {
+        reference.set_cell(row, col, true);
+    }
+    universe.run_headless(10);
+    reference.run_headless(10);
+    assert_eq!(universe.live_cells(), reference.live_cells());
+}
+
+#[test]
+fn row_neighbor_counts_match_per_cell_counts() {
+    for (width, height) in [(3, 3), (4, 7), (16, 16), (31, 5), (70, 12)] {
+        for boundary in [BoundaryMode::Toroidal, BoundaryMode::Dead] {
+            for seed in 0..4 {
+                let mut universe = Universe::new_with_seed(width, height, Implementation::Naive, seed);
+                universe.set_boundary_mode(boundary);
+                let per_cell: Vec<u8> = (0..height)
+                    .flat_map(|row| (0..width).map(move |col| (row, col)))
+                    .map(|(row, col)| universe.neighbor_count(row, col) as u8)
+                    .collect();
+                assert_eq!(universe.neighbor_counts(), per_cell, "{}x{} {:?} seed {}", width, height, boundary, seed);
+            }
+        }
+    }
+}
+
+#[test]
+fn row_counting_ticks_match_the_neighbor_table() {
+    for (width, height) in [(3, 3), (16, 16), (31, 5), (70, 12)] {
+        for implementation in [Implementation::Naive, Implementation::Parallel] {
+            for seed in 0..3 {
+                let mut reference = Universe::new_with_seed(width, height, implementation, seed);
+                let mut universe = Universe::new_with_seed(width, height, implementation, seed);
+                reference.precompute_neighbors(true);
+                for _ in 0..20 {
+                    reference.tick();
+                    universe.tick();
+                    assert_eq!(universe.live_cells(), reference.live_cells());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn array_and_hashset_neighbor_counts_agree_on_every_cell() {
+    for (width, height) in [(1, 1), (1, 4), (2, 2), (2, 5), (3, 3), (8, 5), (17, 11)] {
+        for boundary in [BoundaryMode::Toroidal, BoundaryMode::Dead] {
+            for dedup in [false, true] {
+                for seed in 0..10 {
+                    let mut array = Universe::new_with_seed(width, height, Implementation::Naive, seed);
+                    let mut hashset = Universe::new_with_seed(width, height, Implementation::HashSet, seed);
+                    for universe in [&mut array, &mut hashset] {
+                        universe.set_boundary_mode(boundary);
+                        universe.set_dedup_neighbors(dedup);
+                    }
+                    for row in 0..height {
+                        for col in 0..width {
+                            assert_eq!(
+                                array.neighbor_count(row, col),
+                                hashset.neighbor_count(row, col),
+                                "({}, {}) of {}x{} {:?} dedup {} seed {}",
+                                row,
+                                col,
+                                width,
+                                height,
+                                boundary,
+                                dedup,
+                                seed
+                            );
+                        }
+                    }
+                    assert_same_evolution(width, height, seed, 5, (Implementation::Naive, Implementation::HashSet), |universe| {
+                        universe.set_boundary_mode(boundary);
+                        universe.set_dedup_neighbors(dedup);
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn neighbor_table_ticks_like_the_hashset_and_follows_resizes() {
+    for (width, height) in [(3, 3), (10, 7), (33, 20)] {
+        assert_same_evolution(width, height, 4, 30, (Implementation::HashSet, Implementation::Naive), |universe| {
+            universe.precompute_neighbors(true)
+        });
+    }
+
+    let mut reference = Universe::new_with_seed(12, 9, Implementation::HashSet, 6);
+    let mut universe = Universe::new_with_seed(12, 9, Implementation::Naive, 6);
+    universe.precompute_neighbors(true);
+    for (width, height) in [(20, 15), (5, 4), (2, 2), (9, 9)] {
+        reference.resize(width, height);
+        universe.resize(width, height);
+        for _ in 0..10 {
+            reference.tick();
+            universe.tick();
+            assert_eq!(universe.live_cells(), reference.live_cells(), "after resizing to {}x{}", width, height);
+        }
+    }
+}
+
+#[test]
+fn neighbor_counts_of_a_blinker() {
+    let mut universe = Universe::empty(5, 5, Implementation::Naive);
+    universe.insert_named_pattern("blinker", 2, 1);
+    let expected = [
+        0, 0, 0, 0, 0,
+        1, 2, 3, 2, 1,
+        1, 1, 2, 1, 1,
+        1, 2, 3, 2, 1,
+        0, 0, 0, 0, 0,
+    ];
+    assert_eq!(universe.neighbor_counts(), expected);
+    for (index, &count) in expected.iter().enumerate() {
+        assert_eq!(universe.neighbor_count(index as u32 / 5, index as u32 % 5), count as NeighborCount);
+    }
+}
+
+#[test]
+fn identically_seeded_hashset_runs_hash_alike() {
+    let hashes = |implementation| {
+        let mut universe = Universe::new_with_seed(40, 30, implementation, 12);
+        (0..20)
+            .map(|_| {
+                universe.tick();
+                universe.state_hash()
+            })
+            .collect::<Vec<u64>>()
+    };
+    let first = hashes(Implementation::HashSet);
+    assert_eq!(hashes(Implementation::HashSet), first);
+    // The hash does not depend on how the cells are stored either
+    assert_eq!(hashes(Implementation::Naive), first);
+}
+
+#[test]
+fn toroidal_and_bounded_counts_differ_across_the_edge() {
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        // A blinker along the left edge and one cell in the top right corner
+        let mut universe = Universe::empty(6, 5, implementation);
+        for (row, col) in [(1, 0), (2, 0), (3, 0), (0, 5)] {
+            universe.set_cell(row, col, true);
+        }
+        assert_eq!(universe.neighbor_count_bounded(2, 5), 0);
+        assert_eq!(universe.neighbor_count_toroidal(2, 5), 3);
+        assert_eq!(universe.neighbor_count_bounded(4, 0), 1);
+        assert_eq!(universe.neighbor_count_toroidal(4, 0), 2);
+        // Away from the edges both agree
+        assert_eq!(universe.neighbor_count_bounded(2, 1), 3);
+        assert_eq!(universe.neighbor_count_toroidal(2, 1), 3);
+
+        // Whatever the boundary mode says
+        universe.set_boundary_mode(BoundaryMode::Dead);
+        assert_eq!(universe.neighbor_count_toroidal(2, 5), 3);
+        assert_eq!(universe.neighbor_count(2, 5), 0);
+    }
+}
+
+#[test]
+fn hashset_is_not_transposed_on_non_square_grids() {
+    let mut universe = Universe::empty(13, 5, Implementation::HashSet);
+    universe.set_cell(1, 11, true);
+    assert!(universe.get_cell(1, 11));
+    assert!(!universe.get_cell(11, 1));
+    assert!(universe.live_set().unwrap().contains(&(11, 1)), "the live set is keyed by (col, row)");
+
+    for (width, height) in [(13, 5), (5, 13), (40, 3)] {
+        assert_same_evolution(width, height, 21, 25, (Implementation::Naive, Implementation::HashSet), |_| {});
+    }
+}
+
+#[test]
+fn second_order_ticks_back_to_the_start() {
+    let mut universe = Universe::new_with_seed(24, 16, Implementation::SecondOrder, 13);
+    let mut states = vec![universe.live_cells()];
+    for _ in 0..30 {
+        universe.tick();
+        states.push(universe.live_cells());
+    }
+    for generation in (0..30).rev() {
+        assert!(universe.tick_back());
+        assert_eq!(universe.generation(), generation);
+        assert_eq!(universe.live_cells(), states[generation as usize], "generation {}", generation);
+    }
+    assert!(!universe.tick_back());
+
+    let mut naive = Universe::new_with_seed(24, 16, Implementation::Naive, 13);
+    naive.tick();
+    assert!(!naive.tick_back());
+    assert_eq!(naive.generation(), 1);
+}
+
+#[test]
+fn array_implementations_reuse_the_back_buffer() {
+    for implementation in [Implementation::Naive, Implementation::Parallel] {
+        assert_same_evolution(64, 48, 3, 50, (Implementation::HashSet, implementation), |_| {});
+
+        // Only the first tick allocates the next generation, later ones swap buffers
+        let mut universe = Universe::new_with_seed(64, 48, implementation, 3);
+        universe.enable_profiling(true);
+        for _ in 0..10 {
+            universe.tick();
+        }
+        assert_eq!(universe.profiling_counters().allocations_avoided, 9, "{:?}", implementation);
+    }
+}
+
+#[test]
+fn implementation_getter_returns_the_constructed_variant() {
+    for implementation in IMPLEMENTATIONS {
+        assert_eq!(Universe::empty(4, 4, implementation).implementation(), &implementation);
+        assert_eq!(Universe::new_with_seed(4, 4, implementation, 1).implementation(), &implementation);
+    }
+}
+
+#[test]
+fn brians_brain_decays_as_worked_out_by_hand() {
+    // B2/S/C3: a dead cell with exactly two live neighbors is born (1), live cells start
+    // dying (2) and are dead (0) one generation later. Dying cells are not live neighbors
+    let generations = [
+        "......\n......\n..11..\n......\n......\n......",
+        "......\n..11..\n..22..\n..11..\n......\n......",
+        "..11..\n..22..\n.1..1.\n..22..\n..11..\n......",
+        "..22..\n.1..1.\n.2..2.\n.1..1.\n..22..\n..11..",
+    ];
+    let states = |universe: &Universe| -> String {
+        let rows: Vec<String> = (0..6)
+            .map(|row| {
+                (0..6)
+                    .map(|col| match universe.cell_state(row, col) {
+                        0 => '.',
+                        state => char::from(b'0' + state),
+                    })
+                    .collect()
+            })
+            .collect();
+        rows.join("\n")
+    };
+    for implementation in IMPLEMENTATIONS {
+        let mut universe = Universe::empty(6, 6, implementation);
+        universe.set_rule_string("B2/S/C3").unwrap();
+        universe.set_boundary_mode(BoundaryMode::Dead);
+        universe.set_cell(2, 2, true);
+        universe.set_cell(2, 3, true);
+        for (generation, expected) in generations.iter().enumerate() {
+            assert_eq!(states(&universe), *expected, "{:?} at generation {}", implementation, generation);
+            universe.tick();
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::{env, fs, io, process};
+
+use game_of_life::{write_state_to_file, write_state_to_image, write_state_to_rle, BoundaryMode, Implementation, ParseError, Pattern, RleLimits, Universe};
+
+const MISSING: &str = "tests/does-not-exist";
+
+fn assert_not_found<T>(result: Result<T, ParseError>) {
+    match result {
+        Err(ParseError::Io { path, kind, .. }) => {
+            assert_eq!(path, MISSING);
+            assert_eq!(kind, io::ErrorKind::NotFound);
+        }
+        Err(error) => panic!("expected an IO error, got {}", error),
+        Ok(_) => panic!("expected an IO error, got a universe"),
+    }
+}
+
+#[test]
+fn missing_files_are_errors() {
+    let implementation = Implementation::Naive;
+    assert_not_found(Universe::from_seed_file(5, 5, implementation, MISSING, false));
+    assert_not_found(Universe::from_file(MISSING, implementation));
+    assert_not_found(Universe::from_pbm(MISSING, implementation));
+    assert_not_found(Universe::from_rle(MISSING, implementation));
+    assert_not_found(Universe::from_macrocell(MISSING, implementation));
+    assert_not_found(Universe::from_multi(MISSING, implementation));
+}
+
+/// The line and column of a syntax error, panicking on success or an IO error.
+fn error_location<T>(result: Result<T, ParseError>) -> (usize, usize) {
+    match result {
+        Err(ParseError::Syntax { line, column, .. }) => (line, column),
+        Err(error) => panic!("expected a syntax error, got {}", error),
+        Ok(_) => panic!("expected a syntax error, got a universe"),
+    }
+}
+
+#[test]
+fn strict_seeds_point_at_the_offending_character() {
+    let stray = "4 3 0\n....\n.OXO\n....\n";
+    let result = Universe::from_seed_str(4, 3, Implementation::Naive, stray, true);
+    assert_eq!(error_location(result), (3, 3));
+
+    let long = "4 3 0\n....\n.OO..\n....\n";
+    let result = Universe::from_seed_str(4, 3, Implementation::Naive, long, true);
+    assert_eq!(error_location(result), (3, 5));
+
+    // Outside of strict mode both are ignored
+    for seed in [stray, long] {
+        let universe = Universe::from_seed_str(4, 3, Implementation::Naive, seed, false).unwrap();
+        assert_eq!(universe.population(), 2);
+    }
+}
+
+/// A path in the temporary directory, unique to this test process.
+fn scratch_file(name: &str) -> String {
+    env::temp_dir().join(format!("game-of-life-{}-{}", process::id(), name)).to_string_lossy().into_owned()
+}
+
+#[test]
+fn pbm_images_load_back_as_seeds() {
+    let universe = Universe::new_with_seed(13, 6, Implementation::Naive, 9);
+    let path = scratch_file("round-trip.pbm");
+    write_state_to_image(&universe, &path).unwrap();
+
+    let loaded = Universe::from_pbm(&path, Implementation::HashSet).unwrap();
+    assert_eq!((loaded.width(), loaded.height()), (13, 6));
+    assert_eq!(loaded.live_cells(), universe.live_cells());
+}
+
+#[test]
+fn multi_pattern_files_place_every_block() {
+    let path = scratch_file("two-gliders.txt");
+    fs::write(&path, "20 16 0\n@pattern 1 1\n.O.\n..O\nOOO\n@pattern 10 12\n.O.\nO..\nOOO\n").unwrap();
+
+    let universe = Universe::from_multi(&path, Implementation::Naive).unwrap();
+    assert_eq!((universe.width(), universe.height()), (20, 16));
+    assert_eq!(universe.population(), 10);
+    universe.assert_eq_pattern_at(1, 1, ".O.\n..O\nOOO");
+    universe.assert_eq_pattern_at(10, 12, ".O.\nO..\nOOO");
+}
+
+#[test]
+fn pasted_gliders_are_recognized_in_every_format() {
+    let pastes = [
+        "#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n",
+        "x = 3, y = 3\nbo$2bo$3o!",
+        "!Name: Glider\n.O.\n..O\nOOO\n",
+        ".O.\n..O\nOOO",
+        "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n",
+    ];
+    let mut reference = Universe::empty(10, 10, Implementation::Naive);
+    reference.insert_named_pattern("glider", 4, 5);
+    for text in pastes {
+        let mut universe = Universe::empty(10, 10, Implementation::Naive);
+        universe.insert_auto(text, 4, 5).unwrap();
+        assert_eq!(universe.live_cells(), reference.live_cells(), "pasting {:?}", text);
+    }
+
+    let mut universe = Universe::empty(10, 10, Implementation::Naive);
+    assert!(universe.insert_auto("glider please", 4, 5).is_err());
+    assert!(universe.is_empty());
+}
+
+#[test]
+fn absurd_rle_runs_are_rejected() {
+    let error = Pattern::from_rle("x = 1, y = 1\n99999999999999999999o!").unwrap_err();
+    assert_eq!(error.message(), "Run count exceeds the maximum of 1048576");
+
+    let limits = RleLimits { max_run_length: 100, max_cells: 150 };
+    assert!(Pattern::from_rle_with_limits("100o$100b!", limits).is_ok());
+    assert!(Pattern::from_rle_with_limits("101o!", limits).is_err());
+    let error = Pattern::from_rle_with_limits("100o$100o!", limits).unwrap_err();
+    assert_eq!(error, ParseError::Syntax { line: 1, column: 9, message: "More than the maximum of 150 live cells".to_string() });
+}
+
+#[test]
+fn from_file_takes_the_dimensions_from_the_header() {
+    let path = scratch_file("sized.txt");
+    fs::write(&path, "7 3 12\n.......\n..OOO..\n.......\n").unwrap();
+    let universe = Universe::from_file(&path, Implementation::HashSet).unwrap();
+    assert_eq!((universe.width(), universe.height()), (7, 3));
+    assert_eq!(universe.generation(), 12);
+    assert_eq!(universe.live_cells(), [(1, 2), (1, 3), (1, 4)]);
+}
+
+#[test]
+fn saved_states_keep_their_rule_and_boundary() {
+    let mut universe = Universe::new_with_seed(16, 12, Implementation::Naive, 4);
+    universe.set_rule_string("B36/S23").unwrap();
+    universe.set_boundary_mode(BoundaryMode::Dead);
+    universe.game_of_life(7);
+    let path = scratch_file("highlife.txt");
+    write_state_to_file(&universe, &path, universe.generation() as u32).unwrap();
+    assert!(fs::read_to_string(&path).unwrap().starts_with("16 12 7 B36/S23 dead\n"));
+
+    let mut loaded = Universe::from_file(&path, Implementation::Naive).unwrap();
+    assert_eq!(loaded.rule_string(), "B36/S23");
+    assert_eq!(loaded.boundary_mode(), BoundaryMode::Dead);
+    assert_eq!(loaded.generation(), 7);
+    assert_eq!(loaded.live_cells(), universe.live_cells());
+    universe.tick();
+    loaded.tick();
+    assert_eq!(loaded.live_cells(), universe.live_cells());
+}
+
+#[test]
+fn star_plaintext_patterns_load() {
+    let glider = Pattern::from_plaintext("!Name: Glider\n.*.\n..*\n***\n").unwrap();
+    assert_eq!(glider.cells(), Pattern::named("glider").unwrap().cells());
+
+    let seed = Universe::from_seed_str(3, 3, Implementation::Naive, "3 3 0\n.*.\n..*\n***\n", true).unwrap();
+    seed.assert_eq_pattern(".O.\n..O\nOOO");
+
+    // Other symbols can be configured, anything else is dead unless strict
+    let custom = Pattern::from_plaintext_with("#.\n.#x\n", &['#'], false).unwrap();
+    assert_eq!(custom.cells(), [(0, 0), (1, 1)]);
+    assert!(Pattern::from_plaintext_with("#.\n.#x\n", &['#'], true).is_err());
+}
+
+#[test]
+fn saved_generations_resume_where_they_left_off() {
+    let mut universe = Universe::new_with_seed(8, 6, Implementation::Naive, 9);
+    universe.set_generation(100);
+    assert_eq!((universe.generation(), universe.last_change_generation()), (100, 100));
+    let path = scratch_file("generation-100.txt");
+    write_state_to_file(&universe, &path, universe.generation() as u32).unwrap();
+
+    let mut loaded = Universe::from_file(&path, Implementation::HashSet).unwrap();
+    assert_eq!(loaded.generation(), 100);
+    loaded.tick();
+    assert_eq!(loaded.generation(), 101);
+
+    let content = fs::read_to_string(&path).unwrap();
+    let loaded = Universe::from_seed_str(8, 6, Implementation::Simd, &content, true).unwrap();
+    assert_eq!(loaded.generation(), 100);
+}
+
+#[test]
+fn macrocell_files_decode_like_the_equivalent_rle() {
+    // A glider in the north-west leaf and a block in the south-east one of a 16x16 node
+    let macrocell = "[M2] (golly 4.2)\n#R B3/S23\n#G 42\n.*$..*$***$\n$$$**$**$\n4 1 0 0 2\n";
+    let rle = "x = 10, y = 13, rule = B3/S23\nbo$2bo$3o9$8b2o$8b2o!\n";
+    let sorted = |pattern: Pattern| {
+        let mut cells = pattern.cells().to_vec();
+        cells.sort();
+        cells
+    };
+    assert_eq!(sorted(Pattern::from_macrocell(macrocell).unwrap()), sorted(Pattern::from_rle(rle).unwrap()));
+
+    let (macrocell_path, rle_path) = (scratch_file("glider-and-block.mc"), scratch_file("glider-and-block.rle"));
+    fs::write(&macrocell_path, macrocell).unwrap();
+    fs::write(&rle_path, rle).unwrap();
+    let from_macrocell = Universe::from_macrocell(&macrocell_path, Implementation::HashSet).unwrap();
+    let from_rle = Universe::from_rle(&rle_path, Implementation::HashSet).unwrap();
+    assert_eq!(from_macrocell.population(), 9);
+    assert_eq!(from_macrocell.population(), from_rle.population());
+    assert_eq!(from_macrocell.generation(), 42);
+    assert_eq!(from_macrocell.rule_string(), "B3/S23");
+}
+
+#[test]
+fn ascii_patterns_become_relative_coordinates() {
+    let glider = Pattern::from_ascii(
+        "
+        .O.
+        ..O
+        OOO
+        ",
+    );
+    assert_eq!(glider.cells(), [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+    assert_eq!(glider, Pattern::named("glider").unwrap());
+    assert_eq!(Pattern::from_ascii(".*.\n..*\n***").cells(), glider.cells());
+
+    let mut universe = Universe::empty(6, 6, Implementation::HashSet);
+    universe.insert_pattern(&glider, 2, 3);
+    assert_eq!(universe.live_cells(), [(2, 4), (3, 5), (4, 3), (4, 4), (4, 5)]);
+}
+
+#[test]
+#[should_panic(expected = "Unexpected character 'x' in pattern line 2")]
+fn ascii_patterns_reject_other_characters() {
+    Pattern::from_ascii(".O.\n.x.");
+}
+
+#[test]
+fn rle_metadata_survives_a_round_trip() {
+    let path = scratch_file("metadata.rle");
+    let rle = "#N Glider\n#O Richard K. Guy\n#C The smallest spaceship.\n#C Found in 1969.\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+    fs::write(&path, rle).unwrap();
+    let universe = Universe::from_rle(&path, Implementation::Naive).unwrap();
+    assert_eq!(universe.pattern_name(), Some("Glider"));
+    assert_eq!(universe.author(), Some("Richard K. Guy"));
+    assert_eq!(universe.comment(), Some("The smallest spaceship.\nFound in 1969."));
+
+    let exported = scratch_file("metadata-exported.rle");
+    write_state_to_rle(&universe, &exported).unwrap();
+    let reloaded = Universe::from_rle(&exported, Implementation::HashSet).unwrap();
+    assert_eq!(reloaded.pattern_name(), universe.pattern_name());
+    assert_eq!(reloaded.author(), universe.author());
+    assert_eq!(reloaded.comment(), universe.comment());
+    assert_eq!(reloaded.live_cells(), universe.live_cells());
+}
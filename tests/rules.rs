@@ -0,0 +1,176 @@
+use game_of_life::{Implementation, IsotropicRule, NeighborCount, Rule, TransitionCounts, Universe};
+
+/// Conway's Life with every configuration of each neighbor count spelled out in Hensel notation.
+const CONWAY_ISOTROPIC: &str = "B3aceijknqry/S2aceikn3aceijknqry";
+
+#[test]
+fn conway_in_isotropic_notation_is_conway() {
+    let conway: Rule = "B3/S23".parse().unwrap();
+    let isotropic: IsotropicRule = CONWAY_ISOTROPIC.parse().unwrap();
+    assert_eq!(isotropic, IsotropicRule::from(conway));
+    assert_eq!(isotropic.to_string(), "B3/S23");
+}
+
+#[test]
+fn conway_in_isotropic_notation_ticks_like_conway() {
+    for seed in 0..5 {
+        let mut reference = Universe::new_with_seed(24, 16, Implementation::Naive, seed);
+        let mut universe = Universe::new_with_seed(24, 16, Implementation::Naive, seed);
+        universe.set_rule_string(CONWAY_ISOTROPIC).unwrap();
+        for generation in 1..=20 {
+            reference.tick();
+            universe.tick();
+            assert_eq!(universe.live_cells(), reference.live_cells(), "seed {} generation {}", seed, generation);
+        }
+    }
+}
+
+#[test]
+fn transition_table_can_be_conway_or_its_complement() {
+    let mut conway = Universe::new_with_seed(20, 12, Implementation::Naive, 3);
+    let mut table = Universe::new_with_seed(20, 12, Implementation::Naive, 3);
+    let survival = [false, false, true, true, false, false, false, false, false];
+    let birth = [false, false, false, true, false, false, false, false, false];
+    table.set_transition_table(survival, birth);
+
+    // The complementary rule evolves the inverted grid into the inverse of Conway's
+    let mut inverted = Universe::empty(20, 12, Implementation::Naive);
+    for row in 0..12 {
+        for col in 0..20 {
+            inverted.set_cell(row, col, !conway.get_cell(row, col));
+        }
+    }
+    let (mut complement_survival, mut complement_birth) = ([false; 9], [false; 9]);
+    for count in 0..9 {
+        complement_survival[count] = !birth[8 - count];
+        complement_birth[count] = !survival[8 - count];
+    }
+    inverted.set_transition_table(complement_survival, complement_birth);
+    assert_eq!(inverted.rule_string(), "B0123478/S01234678");
+
+    for generation in 1..=10 {
+        conway.tick();
+        table.tick();
+        inverted.tick();
+        assert_eq!(table.live_cells(), conway.live_cells(), "generation {}", generation);
+        for row in 0..12 {
+            for col in 0..20 {
+                assert_ne!(inverted.get_cell(row, col), conway.get_cell(row, col), "generation {}", generation);
+            }
+        }
+    }
+}
+
+#[test]
+fn neighbor_counts_hold_large_neighborhoods() {
+    let mut universe = Universe::empty(7, 7, Implementation::Naive);
+    for row in 0..7 {
+        for col in 0..7 {
+            universe.set_cell(row, col, true);
+        }
+    }
+    let count: NeighborCount = universe.neighbor_count(3, 3);
+    assert_eq!(count, 8);
+
+    // The radius-3 neighborhood of the center covers the whole grid
+    let radius_3: NeighborCount = (0..7)
+        .flat_map(|row| (0..7).map(move |col| (row, col)))
+        .filter(|&cell| cell != (3, 3))
+        .map(|(row, col)| universe.get_cell(row, col) as NeighborCount)
+        .sum();
+    assert_eq!(radius_3, 48);
+
+    // Up to the documented radius of 127 without overflowing
+    let side: NeighborCount = 2 * 127 + 1;
+    assert_eq!(side.checked_mul(side).map(|cells| cells - 1), Some(65_024));
+}
+
+#[test]
+fn standard_rules_round_trip() {
+    let rules = ["B3/S23", "B36/S23", "B2/S", "B3678/S34678", "B368/S245", "B1357/S1357", "B/S012345678"];
+    for rulestring in rules {
+        let mut universe = Universe::empty(4, 4, Implementation::Naive);
+        universe.set_rule_string(rulestring).unwrap();
+        assert_eq!(universe.rule_string(), rulestring);
+        assert_eq!(rulestring.parse::<Rule>().unwrap().to_string(), rulestring);
+    }
+
+    // Other spellings are formatted canonically, which then parses to the same rule
+    for (rulestring, canonical) in [("S23/B36", "B36/S23"), ("b3/s32", "B3/S23")] {
+        let rule: Rule = rulestring.parse().unwrap();
+        assert_eq!(rule.to_string(), canonical);
+        assert_eq!(canonical.parse::<Rule>().unwrap(), rule);
+    }
+}
+
+#[test]
+fn dedup_counts_each_distinct_neighbor_once() {
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        let mut universe = Universe::empty(2, 2, implementation);
+        for row in 0..2 {
+            for col in 0..2 {
+                universe.set_cell(row, col, true);
+            }
+        }
+        // Without deduplication the other three cells are each counted several times
+        assert_eq!(universe.neighbor_count(0, 0), 8);
+
+        universe.set_dedup_neighbors(true);
+        for row in 0..2 {
+            for col in 0..2 {
+                assert_eq!(universe.neighbor_count(row, col), 3, "{:?}", implementation);
+            }
+        }
+        // Every cell has 3 neighbors and survives
+        universe.tick();
+        assert_eq!(universe.population(), 4);
+    }
+}
+
+#[test]
+fn rule_fn_can_implement_conway() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Parallel] {
+        let mut reference = Universe::new_with_seed(20, 14, implementation, 8);
+        let mut universe = Universe::new_with_seed(20, 14, implementation, 8);
+        universe.set_rule_fn(|alive, neighbors| neighbors == 3 || (alive && neighbors == 2));
+        for _ in 0..10 {
+            reference.tick();
+            universe.tick();
+            assert_eq!(universe.live_cells(), reference.live_cells(), "{:?}", implementation);
+        }
+
+        // Without the function, the configured rule applies again
+        universe.set_rule_fn(|_, _| false);
+        universe.clear_rule_fn();
+        reference.tick();
+        universe.tick();
+        assert_eq!(universe.live_cells(), reference.live_cells(), "{:?}", implementation);
+    }
+}
+
+#[test]
+fn transitions_of_a_blinker() {
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        let mut universe = Universe::empty(8, 8, implementation);
+        universe.insert_named_pattern("blinker", 3, 3);
+        universe.track_transitions(true);
+        // The ends have a single neighbor and starve, the middle survives with two
+        let expected = TransitionCounts { births: 2, survivals: 1, underpopulation_deaths: 2, overpopulation_deaths: 0 };
+        for _ in 0..3 {
+            universe.tick();
+            assert_eq!(universe.last_transition_counts(), expected, "{:?}", implementation);
+        }
+    }
+
+    // Five cells in a plus: the middle has four neighbors and is overcrowded
+    let mut universe = Universe::empty(8, 8, Implementation::Naive);
+    for (row, col) in [(2, 3), (3, 2), (3, 3), (3, 4), (4, 3)] {
+        universe.set_cell(row, col, true);
+    }
+    universe.track_transitions(true);
+    universe.tick();
+    assert_eq!(
+        universe.last_transition_counts(),
+        TransitionCounts { births: 4, survivals: 4, underpopulation_deaths: 0, overpopulation_deaths: 1 }
+    );
+}
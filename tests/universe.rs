@@ -0,0 +1,811 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use game_of_life::{BoundaryMode, BufferSizeMismatch, DimensionMismatch, Implementation, ProfileReport, SeedKind, TickEvents, TickStats, Universe};
+
+#[test]
+fn shrink_to_fit_releases_the_previous_generation() {
+    let mut universe = Universe::new_with_seed(100, 100, Implementation::HashSet, 1);
+    universe.tick();
+    universe.randomize_region(0, 0, 100, 100, 0.0, 0);
+    universe.tick();
+    let before = universe.memory_estimate().bytes;
+    universe.shrink_to_fit();
+    assert!(universe.memory_estimate().bytes < before / 2);
+}
+
+#[test]
+fn merge_into_an_empty_grid_does_nothing() {
+    let mut glider = Universe::empty(5, 5, Implementation::Naive);
+    glider.insert_named_pattern("glider", 0, 0);
+    for (width, height) in [(0, 0), (0, 5), (5, 0)] {
+        let mut universe = Universe::empty(width, height, Implementation::Naive);
+        universe.merge(&glider, 0, 0);
+        assert!(universe.live_cells().is_empty());
+    }
+}
+
+#[test]
+fn merge_places_the_other_universe_at_the_offset() {
+    let mut glider = Universe::empty(5, 5, Implementation::Naive);
+    glider.insert_named_pattern("glider", 0, 0);
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Simd] {
+        let mut universe = Universe::empty(20, 16, implementation);
+        universe.insert_named_pattern("block", 1, 1);
+        universe.merge(&glider, 7, 9);
+        assert_eq!(universe.population(), 4 + 5);
+        universe.assert_eq_pattern_at(1, 1, "OO\nOO");
+        universe.assert_eq_pattern_at(6, 8, ".....\n..O..\n...O.\n.OOO.\n.....");
+
+        // Past the south-east corner the glider wraps around to the other edges
+        let mut universe = Universe::empty(20, 16, implementation);
+        universe.merge(&glider, 14, 18);
+        assert_eq!(universe.live_cells(), [(0, 0), (0, 18), (0, 19), (14, 19), (15, 0)]);
+
+        // or is clipped when the edges are dead
+        let mut universe = Universe::empty(20, 16, implementation);
+        universe.set_boundary_mode(BoundaryMode::Dead);
+        universe.merge(&glider, 14, 18);
+        assert_eq!(universe.live_cells(), [(14, 19)]);
+    }
+}
+
+#[test]
+fn step_within_ticks_at_least_once() {
+    let mut universe = Universe::new_with_seed(64, 64, Implementation::Naive, 1);
+    assert_eq!(universe.step_within(0.0), 1);
+    let ticks = universe.step_within(0.001);
+    assert!(ticks >= 1);
+    assert_eq!(universe.generation(), 1 + ticks as u64);
+}
+
+#[test]
+fn step_within_respects_a_generous_budget() {
+    let mut universe = Universe::new_with_seed(32, 32, Implementation::Naive, 1);
+    let start = Instant::now();
+    let ticks = universe.step_within(50.0);
+    let elapsed = start.elapsed();
+    assert!(ticks > 1);
+    assert!(elapsed < Duration::from_millis(250), "took {:?}", elapsed);
+}
+
+#[test]
+fn cell_state_is_zero_outside_the_grid() {
+    let mut universe = Universe::empty(4, 3, Implementation::Naive);
+    universe.set_rule_string("B2/S/C3").unwrap();
+    universe.set_cell(1, 1, true);
+    universe.tick();
+    assert_eq!(universe.cell_state(1, 1), 2);
+    for (row, col) in [(3, 0), (0, 4), (u32::MAX, 0), (0, u32::MAX), (u32::MAX, u32::MAX)] {
+        assert_eq!(universe.cell_state(row, col), 0);
+    }
+}
+
+#[test]
+fn reset_forgets_the_previous_generation() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::SecondOrder, Implementation::Simd] {
+        for reset in [Universe::reset, Universe::reset_to_seed] {
+            let mut universe = Universe::new_with_seed(12, 9, implementation, 3);
+            universe.tick();
+            reset(&mut universe);
+            assert!(universe.changed_cells().is_empty(), "{:?}", implementation);
+            assert_eq!(universe.population_delta(), 0);
+            assert_eq!(universe.generation(), 0);
+            for (row, col) in [(0, 0), (4, 5), (8, 11)] {
+                let alive = if universe.get_cell(row, col) { 1.0 } else { 0.0 };
+                assert_eq!(universe.cell_fade(row, col, 0.0), alive);
+            }
+        }
+    }
+}
+
+#[test]
+fn zero_sized_universes_stay_empty() {
+    let implementations = [
+        Implementation::Naive,
+        Implementation::HashSet,
+        Implementation::Parallel,
+        Implementation::SecondOrder,
+        Implementation::Simd,
+    ];
+    for (width, height) in [(0, 5), (5, 0), (0, 0)] {
+        for implementation in implementations {
+            for boundary in [BoundaryMode::Toroidal, BoundaryMode::Dead] {
+                let mut universe = Universe::new_with_seed(width, height, implementation, 1);
+                universe.set_boundary_mode(boundary);
+                universe.tick();
+                assert_eq!(universe.run_headless(3), 0);
+                for rule in ["B3/S23/C4", "B2n3/S23-q", "B3/S23"] {
+                    universe.set_rule_string(rule).unwrap();
+                    universe.tick();
+                }
+                universe.set_shear(2);
+                universe.precompute_neighbors(true);
+                universe.tick();
+
+                assert_eq!(universe.population(), 0);
+                assert!(universe.live_cells().is_empty());
+                assert_eq!(universe.bounding_box(), None);
+                assert_eq!(universe.neighbor_count(0, 0), 0);
+                assert_eq!(universe.neighbor_count_toroidal(0, 0), 0);
+                assert_eq!(universe.neighbor_count_bounded(0, 0), 0);
+                assert!(universe.connected_components(true).is_empty());
+                assert!(universe.tick_events().births.is_empty());
+                universe.reset();
+                assert_eq!(universe.population(), 0);
+            }
+        }
+    }
+}
+
+#[test]
+fn population_history_records_every_tick() {
+    let mut universe = Universe::empty(8, 8, Implementation::Naive);
+    universe.insert_named_pattern("blinker", 3, 3);
+    universe.tick();
+    assert!(universe.population_history().is_empty(), "history is off by default");
+
+    universe.track_population_history(true);
+    for _ in 0..5 {
+        universe.tick();
+    }
+    assert_eq!(universe.population_history(), [3; 5]);
+    universe.track_population_history(false);
+    assert!(universe.population_history().is_empty());
+}
+
+#[test]
+fn update_ticks_only_while_running() {
+    let mut universe = Universe::new_with_seed(16, 16, Implementation::Naive, 1);
+    universe.set_tps(10.0);
+    assert!(universe.is_running());
+    universe.set_running(false);
+    assert_eq!(universe.update(1000.0), 0);
+    assert_eq!(universe.generation(), 0);
+
+    universe.set_running(true);
+    assert_eq!(universe.update(250.0), 2);
+    // The 50ms left over from the last frame count towards the next tick
+    assert_eq!(universe.update(50.0), 1);
+    assert_eq!(universe.generation(), 3);
+
+    universe.set_running(false);
+    assert_eq!(universe.update(1000.0), 0);
+    assert_eq!(universe.generation(), 3);
+}
+
+#[test]
+fn diff_lists_the_differing_cells() {
+    let mut first = Universe::empty(6, 4, Implementation::Naive);
+    let mut second = Universe::empty(6, 4, Implementation::HashSet);
+    for (row, col) in [(0, 0), (1, 4), (3, 5)] {
+        first.set_cell(row, col, true);
+        second.set_cell(row, col, true);
+    }
+    assert_eq!(first.diff(&second), Ok(vec![]));
+
+    first.set_cell(2, 1, true);
+    second.set_cell(1, 4, false);
+    assert_eq!(first.diff(&second), Ok(vec![(1, 4), (2, 1)]));
+
+    let other = Universe::empty(4, 6, Implementation::Naive);
+    assert_eq!(first.diff(&other), Err(DimensionMismatch { expected: (6, 4), found: (4, 6) }));
+}
+
+#[test]
+fn fast_forward_fires_the_callback_once() {
+    let mut universe = Universe::empty(8, 8, Implementation::Naive);
+    universe.insert_named_pattern("blinker", 3, 3);
+    universe.tick();
+    let stats = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&stats);
+    universe.set_on_tick(move |tick| recorded.lock().unwrap().push(tick));
+
+    universe.fast_forward(100);
+    assert_eq!(universe.generation(), 101);
+    assert_eq!(*stats.lock().unwrap(), [TickStats { generation: 101, population: 3 }]);
+}
+
+#[test]
+fn array_implementations_leave_the_hashset_empty() {
+    for implementation in [Implementation::Naive, Implementation::Parallel, Implementation::Simd] {
+        let mut universe = Universe::new_with_seed(512, 512, implementation, 2);
+        assert_eq!(universe.hashset_capacity(), 0);
+        universe.tick();
+        assert!(universe.live_set().is_none());
+        assert_eq!(universe.hashset_capacity(), 0);
+        assert_eq!(universe.memory_estimate().hashset_capacity, 0);
+    }
+
+    let universe = Universe::new_with_seed(64, 64, Implementation::HashSet, 2);
+    assert_eq!(universe.live_set().map(|set| set.len()), Some(universe.population() as usize));
+}
+
+#[test]
+fn tick_batch_returns_each_population() {
+    let mut universe = Universe::empty(8, 8, Implementation::HashSet);
+    universe.insert_named_pattern("blinker", 3, 3);
+    assert_eq!(universe.tick_batch(4), [3, 3, 3, 3]);
+    assert_eq!(universe.generation(), 4);
+}
+
+#[test]
+fn profile_reports_every_tick() {
+    let mut universe = Universe::new_with_seed(64, 64, Implementation::Naive, 3);
+    let report = universe.profile(20);
+    assert_eq!(report.count, 20);
+    assert_eq!(universe.generation(), 20);
+    assert!(report.min <= report.median && report.median <= report.max);
+    assert!(report.min <= report.mean && report.mean <= report.max);
+    assert!(report.max > Duration::ZERO);
+
+    assert_eq!(universe.profile(0), ProfileReport::default());
+}
+
+#[test]
+fn last_change_generation_stops_at_a_still_life() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Simd] {
+        // Three cells of a block, the fourth is born in the first tick
+        let mut universe = Universe::empty(8, 8, implementation);
+        for (row, col) in [(3, 3), (3, 4), (4, 3)] {
+            universe.set_cell(row, col, true);
+        }
+        universe.tick();
+        assert_eq!(universe.population(), 4);
+        assert_eq!(universe.last_change_generation(), 1);
+        for _ in 0..5 {
+            universe.tick();
+        }
+        assert_eq!(universe.generation(), 6);
+        assert_eq!(universe.last_change_generation(), 1, "{:?}", implementation);
+    }
+}
+
+#[test]
+fn trimmed_string_is_the_bounding_box() {
+    let mut universe = Universe::empty(20, 20, Implementation::HashSet);
+    universe.insert_named_pattern("glider", 11, 7);
+    assert_eq!(universe.bounding_box(), Some((11, 7, 13, 9)));
+    assert_eq!(universe.trimmed_string(), ".O.\n..O\nOOO\n");
+
+    assert_eq!(Universe::empty(20, 20, Implementation::Naive).trimmed_string(), "");
+}
+
+#[test]
+fn set_cells_from_indices_sets_each_index() {
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        let mut universe = Universe::empty(9, 7, implementation);
+        let indices = [0, 8, 9, 31, 40, 62];
+        universe.set_cells_from_indices(&indices, true);
+        universe.set_cells_from_indices(&[63, 1000], true);
+        for index in 0..63 {
+            assert_eq!(universe.get_cell(index / 9, index % 9), indices.contains(&index), "index {}", index);
+        }
+        assert_eq!(universe.population(), 6);
+
+        universe.set_cells_from_indices(&indices[..2], false);
+        assert_eq!(universe.population(), 4);
+    }
+}
+
+#[test]
+fn noise_only_depends_on_its_seed() {
+    let mut first = Universe::new_with_seed(24, 24, Implementation::Naive, 1);
+    let mut second = Universe::new_with_seed(24, 24, Implementation::HashSet, 1);
+    first.inject_noise(30, 77);
+    second.inject_noise(30, 77);
+    assert_eq!(first.live_cells(), second.live_cells());
+
+    let untouched = Universe::new_with_seed(24, 24, Implementation::Naive, 1);
+    assert_eq!(first.diff(&untouched).unwrap().len(), 30);
+    // Flipping other cells on top makes them differ
+    second.inject_noise(30, 78);
+    assert_ne!(second.live_cells(), first.live_cells());
+}
+
+#[test]
+fn editing_outside_of_the_grid_does_nothing() {
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        let mut universe = Universe::new_with_seed(7, 5, implementation, 4);
+        let cells = universe.live_cells();
+        for (row, col) in [(5, 7), (5, 0), (0, 7), (u32::MAX, u32::MAX)] {
+            assert!(!universe.in_bounds(row, col));
+            universe.toggle_cell(row, col);
+            universe.set_cell(row, col, true);
+            assert!(!universe.get_cell(row, col));
+        }
+        assert_eq!(universe.live_cells(), cells, "{:?}", implementation);
+        assert_eq!(universe.population() as usize, cells.len());
+    }
+}
+
+#[test]
+fn estimated_memory_of_each_implementation() {
+    assert_eq!(Universe::estimated_memory(1000, 500, &Implementation::Naive), 1_000_000);
+    assert_eq!(Universe::estimated_memory(1000, 500, &Implementation::Parallel), 1_000_000);
+    assert_eq!(Universe::estimated_memory(1000, 500, &Implementation::HashSet), 500_000);
+    assert_eq!(Universe::estimated_memory(1000, 500, &Implementation::Simd), 1_000_000 + 3 * 62_500);
+    // Large enough to overflow 32 bits
+    assert_eq!(Universe::estimated_memory(u32::MAX, 2, &Implementation::Naive), 4 * u32::MAX as u64);
+}
+
+#[test]
+fn trimmed_shrinks_to_the_live_cells() {
+    let mut universe = Universe::empty(64, 48, Implementation::HashSet);
+    universe.insert_named_pattern("glider", 30, 17);
+    universe.set_boundary_mode(BoundaryMode::Dead);
+    universe.set_rule_string("B36/S23").unwrap();
+    universe.tick();
+
+    let trimmed = universe.trimmed();
+    assert_eq!((trimmed.width(), trimmed.height()), (3, 3));
+    trimmed.assert_eq_pattern("O.O\n.OO\n.O.");
+    assert_eq!(trimmed.generation(), 0);
+    assert_eq!(trimmed.implementation(), &Implementation::HashSet);
+    assert_eq!(trimmed.boundary_mode(), BoundaryMode::Dead);
+    assert_eq!(trimmed.rule_string(), "B36/S23");
+
+    let empty = Universe::empty(64, 48, Implementation::Naive).trimmed();
+    assert_eq!((empty.width(), empty.height(), empty.population()), (1, 1, 0));
+}
+
+#[test]
+fn profiling_counts_every_cell_visited() {
+    let mut universe = Universe::new_with_seed(12, 9, Implementation::Naive, 6);
+    universe.tick();
+    assert_eq!(universe.profiling_counters().cells_visited, 0, "profiling is off by default");
+
+    universe.enable_profiling(true);
+    for _ in 0..5 {
+        universe.tick();
+    }
+    let counters = universe.profiling_counters();
+    assert_eq!(counters.cells_visited, 12 * 9 * 5);
+    assert_eq!(counters.neighbor_lookups, 3 * 12 * 9 * 5);
+
+    universe.enable_profiling(false);
+    assert_eq!(universe.profiling_counters().cells_visited, 0);
+}
+
+#[test]
+fn population_delta_follows_growth_and_decay() {
+    let mut growing = Universe::empty(16, 16, Implementation::Naive);
+    growing.insert_named_pattern("r-pentomino", 6, 6);
+    assert_eq!(growing.population_delta(), 0);
+    growing.tick();
+    assert_eq!(growing.population_delta(), 1);
+
+    // A diagonal of three cells leaves only the middle one
+    let mut decaying = Universe::empty(8, 8, Implementation::HashSet);
+    for cell in 2..5 {
+        decaying.set_cell(cell, cell, true);
+    }
+    decaying.tick();
+    assert_eq!(decaying.population_delta(), -2);
+
+    let mut still = Universe::empty(8, 8, Implementation::Simd);
+    still.insert_named_pattern("block", 3, 3);
+    still.tick();
+    assert_eq!(still.population_delta(), 0);
+}
+
+#[test]
+fn resize_keeps_the_buffers_in_step_with_the_dimensions() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Simd] {
+        let mut universe = Universe::new_with_seed(10, 6, implementation, 2);
+        universe.precompute_neighbors(true);
+        for (width, height) in [(17, 3), (4, 11), (0, 5), (8, 8)] {
+            // Every operation below checks the buffers against the dimensions in debug builds
+            universe.resize(width, height);
+            universe.set_cell(height.saturating_sub(1), width.saturating_sub(1), true);
+            universe.tick();
+            assert_eq!((universe.width(), universe.height()), (width, height));
+            assert_eq!(universe.neighbor_counts().len(), (width * height) as usize);
+        }
+    }
+}
+
+#[test]
+fn toggle_line_flips_each_cell_of_the_stroke_once() {
+    let mut universe = Universe::empty(8, 8, Implementation::HashSet);
+    universe.set_cell(3, 3, true);
+    universe.toggle_line(1, 1, 5, 5);
+    universe.assert_eq_pattern(
+        "
+        ........
+        .O......
+        ..O.....
+        ........
+        ....O...
+        .....O..
+        ........
+        ",
+    );
+
+    // Drawing it again from the other end flips the same cells back
+    universe.toggle_line(5, 5, 1, 1);
+    assert_eq!(universe.live_cells(), [(3, 3)]);
+
+    universe.toggle_line(6, 0, 6, 3);
+    universe.toggle_line(7, 5, 7, 12);
+    assert_eq!(universe.get_row(6), [true, true, true, true, false, false, false, false]);
+    assert_eq!(universe.get_row(7), [false, false, false, false, false, true, true, true]);
+}
+
+#[test]
+fn step_and_render_writes_the_packed_cells() {
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        let mut universe = Universe::new_with_seed(13, 5, implementation, 7);
+        let mut buffer = vec![0xff; universe.packed_len()];
+        assert_eq!(buffer.len(), 9);
+        for generation in 1..=3 {
+            universe.step_and_render(&mut buffer).unwrap();
+            assert_eq!(universe.generation(), generation);
+            assert_eq!(buffer, universe.cells_as_bytes(), "{:?}", implementation);
+        }
+
+        let mut short = vec![0; 8];
+        assert_eq!(universe.step_and_render(&mut short), Err(BufferSizeMismatch { expected: 9, found: 8 }));
+        assert_eq!(universe.generation(), 3);
+    }
+}
+
+#[test]
+fn set_size_clips_and_pads() {
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        let mut universe = Universe::new_with_seed(10, 10, implementation, 3);
+        universe.tick();
+        let kept: Vec<(u32, u32)> = universe.live_cells().into_iter().filter(|&(row, col)| row < 6 && col < 8).collect();
+        universe.set_size(8, 6);
+        assert_eq!((universe.width(), universe.height()), (8, 6));
+        assert_eq!(universe.live_cells(), kept);
+
+        universe.set_size(12, 9);
+        assert_eq!(universe.live_cells(), kept);
+        assert_eq!(universe.generation(), 1);
+    }
+}
+
+#[test]
+fn assert_eq_pattern_accepts_a_match() {
+    let mut universe = Universe::empty(10, 10, Implementation::HashSet);
+    universe.insert_named_pattern("glider", 4, 6);
+    universe.assert_eq_pattern_at(
+        4,
+        6,
+        "
+        .O.
+        ..O
+        OOO
+        ",
+    );
+    universe.assert_eq_pattern("......\n......");
+    // Beyond the edges cells count as dead
+    universe.assert_eq_pattern_at(8, 8, "...\n...");
+}
+
+#[test]
+fn assert_eq_pattern_reports_the_differences() {
+    let mut universe = Universe::empty(10, 10, Implementation::Naive);
+    universe.insert_named_pattern("glider", 4, 6);
+    let message = panic::catch_unwind(AssertUnwindSafe(|| universe.assert_eq_pattern_at(4, 6, ".O.\nO..\nOOO")))
+        .expect_err("a glider facing the other way does not match")
+        .downcast::<String>()
+        .unwrap();
+    assert_eq!(
+        *message,
+        "Pattern at (4, 6) differs in 2 cell(s), expected, actual and differences:\n.O.  .O.\nO..  ..O  X X\nOOO  OOO\n"
+    );
+}
+
+#[test]
+fn as_cells_matches_get_cell() {
+    let mut universe = Universe::new_with_seed(11, 7, Implementation::Naive, 9);
+    universe.tick();
+    let cells = universe.as_cells().unwrap();
+    assert_eq!(cells.len(), 11 * 7);
+    for (row, col) in [(0, 0), (0, 10), (3, 4), (6, 0), (6, 10), (5, 7)] {
+        assert_eq!(cells[(row * 11 + col) as usize], universe.get_cell(row, col));
+    }
+    let expected = cells.to_vec();
+    assert_eq!(universe.into_cells(), expected);
+
+    // The HashSet implementation has no such buffer, but materializes one when consumed
+    let mut hashset = Universe::new_with_seed(11, 7, Implementation::HashSet, 9);
+    hashset.tick();
+    assert!(hashset.as_cells().is_none());
+    assert_eq!(hashset.into_cells(), expected);
+}
+
+#[test]
+fn packed_cells_upload_into_a_fresh_universe() {
+    let mut source = Universe::new_with_seed(13, 5, Implementation::Naive, 4);
+    source.tick();
+    let bytes = source.cells_as_bytes();
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        let mut universe = Universe::empty(13, 5, implementation);
+        universe.set_cells_from_bytes(&bytes).unwrap();
+        assert!(universe.equals(&source), "{:?}", implementation);
+        assert_eq!(universe.cells_as_bytes(), bytes);
+
+        // Unused bits of the last byte are ignored
+        let mut padded = bytes.clone();
+        *padded.last_mut().unwrap() |= 0b1110_0000;
+        universe.set_cells_from_bytes(&padded).unwrap();
+        assert!(universe.equals(&source));
+
+        assert_eq!(universe.set_cells_from_bytes(&bytes[1..]), Err(BufferSizeMismatch { expected: 9, found: 8 }));
+    }
+}
+
+#[test]
+fn cell_fade_blends_the_last_two_generations() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Simd] {
+        let mut universe = Universe::empty(8, 8, implementation);
+        universe.insert_named_pattern("blinker", 3, 3);
+        universe.tick();
+
+        // (2, 4) was born, (3, 3) died and (3, 4) stayed alive
+        for (alpha, born, died) in [(0.0, 0.0, 1.0), (0.25, 0.25, 0.75), (1.0, 1.0, 0.0), (-1.0, 0.0, 1.0), (2.0, 1.0, 0.0)] {
+            assert_eq!(universe.cell_fade(2, 4, alpha), born, "{:?}", implementation);
+            assert_eq!(universe.cell_fade(3, 3, alpha), died, "{:?}", implementation);
+            assert_eq!(universe.cell_fade(3, 4, alpha), 1.0);
+            assert_eq!(universe.cell_fade(0, 0, alpha), 0.0);
+        }
+    }
+}
+
+#[test]
+fn equals_ignores_the_implementation() {
+    let mut naive = Universe::new_with_seed(20, 15, Implementation::Naive, 8);
+    let mut hashset = Universe::new_with_seed(20, 15, Implementation::HashSet, 8);
+    assert!(naive.equals(&hashset));
+    naive.tick();
+    hashset.tick();
+    assert!(naive.equals(&hashset) && hashset.equals(&naive));
+
+    hashset.toggle_cell(7, 3);
+    assert!(!naive.equals(&hashset));
+    hashset.toggle_cell(7, 3);
+    assert!(naive.equals(&hashset));
+
+    // Same cells on a larger grid
+    let mut larger = Universe::empty(20, 16, Implementation::Naive);
+    for (row, col) in naive.live_cells() {
+        larger.set_cell(row, col, true);
+    }
+    assert!(!naive.equals(&larger));
+}
+
+#[test]
+fn hashset_capacity_follows_growth_and_shrinking() {
+    let mut universe = Universe::empty(64, 64, Implementation::HashSet);
+    let empty = universe.hashset_capacity();
+    let indices: Vec<u32> = (0..64 * 64).step_by(2).collect();
+    universe.set_cells_from_indices(&indices, true);
+    let grown = universe.memory_estimate();
+    assert_eq!(grown.live_cells, 2048);
+    assert!(grown.hashset_capacity >= 2048 && grown.hashset_capacity > empty);
+    assert_eq!(universe.hashset_capacity(), grown.hashset_capacity);
+
+    // Killing the cells keeps the room until it is released
+    universe.set_cells_from_indices(&indices[8..], false);
+    assert_eq!(universe.hashset_capacity(), grown.hashset_capacity);
+    universe.shrink_to_fit();
+    let shrunk = universe.memory_estimate();
+    assert_eq!(shrunk.live_cells, 8);
+    assert!(shrunk.hashset_capacity < 64, "{:?}", shrunk);
+    assert!(shrunk.bytes < grown.bytes);
+}
+
+#[test]
+fn changed_cells_of_a_blinker_are_in_increasing_order() {
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        let mut universe = Universe::empty(8, 8, implementation);
+        universe.insert_named_pattern("blinker", 3, 3);
+        for _ in 0..2 {
+            universe.tick();
+            let changed = universe.changed_cells();
+            assert!(changed.windows(2).all(|pair| pair[0] < pair[1]), "{:?}: {:?}", implementation, changed);
+            assert_eq!(changed, [2 * 8 + 4, 3 * 8 + 3, 3 * 8 + 5, 4 * 8 + 4], "{:?}", implementation);
+        }
+    }
+}
+
+#[test]
+fn tick_events_of_a_blinker() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Simd] {
+        let mut universe = Universe::empty(8, 8, implementation);
+        universe.insert_named_pattern("blinker", 3, 3);
+        let (horizontal_ends, vertical_ends) = (vec![3 * 8 + 3, 3 * 8 + 5], vec![2 * 8 + 4, 4 * 8 + 4]);
+        for _ in 0..2 {
+            let events = universe.tick_events();
+            assert_eq!(events, TickEvents { births: vertical_ends.clone(), deaths: horizontal_ends.clone() });
+            let events = universe.tick_events();
+            assert_eq!(events, TickEvents { births: horizontal_ends.clone(), deaths: vertical_ends.clone() });
+        }
+    }
+}
+
+#[test]
+fn clustered_seeds_stay_near_their_centers() {
+    // A single fully dense cluster is a disc of 81 cells, possibly clipped by an edge
+    let kind = SeedKind::Clustered { clusters: 1, radius: 5, density: 1.0 };
+    for seed in 0..20 {
+        let universe = Universe::new_with_seed_kind(64, 64, Implementation::Naive, kind, seed);
+        let (min_row, min_col, max_row, max_col) = universe.bounding_box().unwrap();
+        assert!(max_row - min_row <= 10 && max_col - min_col <= 10, "seed {}: {:?}", seed, universe.bounding_box());
+        assert!(universe.population() <= 81, "seed {}: {}", seed, universe.population());
+        if min_row > 0 && min_col > 0 && max_row < 63 && max_col < 63 {
+            assert_eq!(universe.population(), 81, "seed {}", seed);
+        }
+    }
+
+    // Sparse clusters cover little of the grid, and a live cell is usually among others
+    let kind = SeedKind::Clustered { clusters: 4, radius: 4, density: 0.8 };
+    let universe = Universe::new_with_seed_kind(64, 64, Implementation::Naive, kind, 7);
+    assert!(universe.population() > 0 && universe.population() <= 4 * 49);
+    let crowded = universe.live_cells().into_iter().filter(|&(row, col)| {
+        (row.saturating_sub(1)..=row + 1)
+            .flat_map(|r| (col.saturating_sub(1)..=col + 1).map(move |c| (r, c)))
+            .filter(|&(r, c)| (r, c) != (row, col) && universe.get_cell(r, c))
+            .count()
+            >= 3
+    });
+    assert!(crowded.count() * 2 > universe.population() as usize);
+
+    let again = Universe::new_with_seed_kind(64, 64, Implementation::Naive, kind, 7);
+    assert_eq!(again.live_cells(), universe.live_cells());
+}
+
+#[test]
+fn rows_and_columns_of_a_glider() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Simd] {
+        let mut universe = Universe::empty(5, 4, implementation);
+        universe.insert_named_pattern("glider", 0, 1);
+        let grid: Vec<Vec<bool>> = (0..4).map(|row| (0..5).map(|col| universe.get_cell(row, col)).collect()).collect();
+        for row in 0..4 {
+            assert_eq!(universe.get_row(row), grid[row as usize], "{:?} row {}", implementation, row);
+        }
+        for col in 0..5 {
+            let column: Vec<bool> = grid.iter().map(|row| row[col as usize]).collect();
+            assert_eq!(universe.get_column(col), column, "{:?} column {}", implementation, col);
+        }
+        assert_eq!(universe.get_row(2), [false, true, true, true, false]);
+        assert_eq!(universe.get_column(2), [true, false, true, false]);
+        assert!(universe.get_row(4).is_empty());
+        assert!(universe.get_column(5).is_empty());
+    }
+}
+
+#[test]
+fn randomize_region_only_touches_the_region() {
+    let inside = |row: u32, col: u32| (5..11).contains(&row) && (8..18).contains(&col);
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Simd] {
+        let before = Universe::new_with_seed(30, 20, implementation, 2);
+        let mut universe = Universe::new_with_seed(30, 20, implementation, 2);
+        universe.randomize_region(5, 8, 6, 10, 0.5, 3);
+        let changed: Vec<(u32, u32)> = (0..20)
+            .flat_map(|row| (0..30).map(move |col| (row, col)))
+            .filter(|&(row, col)| universe.get_cell(row, col) != before.get_cell(row, col))
+            .collect();
+        assert!(!changed.is_empty(), "{:?}", implementation);
+        assert!(changed.iter().all(|&(row, col)| inside(row, col)), "{:?}: {:?}", implementation, changed);
+
+        let mut again = Universe::new_with_seed(30, 20, implementation, 2);
+        again.randomize_region(5, 8, 6, 10, 0.5, 3);
+        assert_eq!(again.live_cells(), universe.live_cells());
+    }
+
+    // A region hanging over the corner is clipped
+    let mut universe = Universe::empty(10, 10, Implementation::HashSet);
+    universe.randomize_region(7, 6, 50, 50, 1.0, 0);
+    assert_eq!(universe.population(), 3 * 4);
+    assert_eq!(universe.bounding_box(), Some((7, 6, 9, 9)));
+}
+
+#[test]
+fn tick_timing_averages_fill_in_once_ticking() {
+    let mut universe = Universe::new_with_seed(256, 256, Implementation::Naive, 5);
+    assert_eq!((universe.avg_tick_ms(), universe.estimated_fps()), (0.0, 0.0));
+
+    assert_eq!(universe.game_of_life(20), 20);
+    let (average, fps) = (universe.avg_tick_ms(), universe.estimated_fps());
+    assert!(average > 0.0 && average.is_finite(), "{}", average);
+    assert!(fps > 0.0 && fps.is_finite(), "{}", fps);
+    assert!((average * fps - 1000.0).abs() < 1e-6, "{} ms at {} fps", average, fps);
+}
+
+#[test]
+fn reset_to_seed_restores_generation_zero() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::SecondOrder, Implementation::Simd] {
+        let seed = Universe::new_with_seed(20, 15, implementation, 8);
+        let mut universe = Universe::new_with_seed(20, 15, implementation, 8);
+        universe.game_of_life(10);
+        universe.set_cell(0, 0, !universe.get_cell(0, 0));
+        assert_ne!(universe.live_cells(), seed.live_cells());
+
+        universe.reset_to_seed();
+        assert_eq!(universe.generation(), 0);
+        assert_eq!(universe.live_cells(), seed.live_cells(), "{:?}", implementation);
+
+        // Reruns take the same path
+        let mut rerun = Universe::new_with_seed(20, 15, implementation, 8);
+        universe.game_of_life(10);
+        rerun.game_of_life(10);
+        assert_eq!(universe.live_cells(), rerun.live_cells());
+    }
+}
+
+#[test]
+fn direct_accessors_agree_with_get_cell() {
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::SecondOrder, Implementation::Simd] {
+        let mut universe = Universe::new_with_seed(23, 17, implementation, 6);
+        universe.game_of_life(5);
+        let cells: Vec<(u32, u32)> = (0..17).flat_map(|row| (0..23).map(move |col| (row, col))).collect();
+        match (universe.cells_slice(), universe.live_set()) {
+            (Some(slice), None) => {
+                assert_eq!(slice.len(), cells.len());
+                for &(row, col) in &cells {
+                    assert_eq!(slice[(row * 23 + col) as usize], universe.get_cell(row, col), "{:?}", implementation);
+                }
+            }
+            (None, Some(set)) => {
+                assert_eq!(set.len(), universe.population() as usize);
+                for &(row, col) in &cells {
+                    assert_eq!(set.contains(&(col, row)), universe.get_cell(row, col));
+                }
+            }
+            accessors => panic!("{:?} exposes {:?}", implementation, accessors),
+        }
+    }
+}
+
+#[test]
+fn patterns_crossing_a_sheared_edge_shift_rows() {
+    // The lwss travels west, two columns every four generations, so after 20 it has left
+    // the west edge behind and, on the twisted torus, comes back in `shear` rows further north
+    for implementation in [Implementation::Naive, Implementation::HashSet, Implementation::Simd] {
+        let mut sheared = Universe::empty(20, 16, implementation);
+        sheared.insert_named_pattern("lwss", 8, 1);
+        sheared.set_shear(3);
+        let mut plain = Universe::empty(20, 16, implementation);
+        plain.insert_named_pattern("lwss", 8, 1);
+        sheared.game_of_life(20);
+        plain.game_of_life(20);
+
+        let (min_row, min_col, _, max_col) = plain.bounding_box().unwrap();
+        assert!(min_col > 10 && max_col < 19 && min_row >= 3, "{:?}", plain.bounding_box());
+        let shifted: Vec<(u32, u32)> = plain.live_cells().into_iter().map(|(row, col)| (row - 3, col)).collect();
+        assert_eq!(sheared.live_cells(), shifted, "{:?}", implementation);
+
+        // The glider travels south-east and comes back in through the west edge further south
+        let mut sheared = Universe::empty(20, 16, implementation);
+        sheared.insert_named_pattern("glider", 2, 15);
+        sheared.set_shear(3);
+        let mut plain = Universe::empty(20, 16, implementation);
+        plain.insert_named_pattern("glider", 2, 15);
+        sheared.game_of_life(32);
+        plain.game_of_life(32);
+
+        let (_, _, max_row, max_col) = plain.bounding_box().unwrap();
+        assert!(max_col < 10 && max_row + 3 < 16, "{:?}", plain.bounding_box());
+        let shifted: Vec<(u32, u32)> = plain.live_cells().into_iter().map(|(row, col)| (row + 3, col)).collect();
+        assert_eq!(sheared.live_cells(), shifted, "{:?}", implementation);
+    }
+}
+
+#[test]
+fn tick_log_lines_carry_all_three_fields() {
+    let line = TickStats { generation: 123, population: 456 }.log_line(0.84);
+    assert_eq!(line, "gen=123 pop=456 dt=0.8ms");
+
+    let mut universe = Universe::empty(8, 8, Implementation::HashSet);
+    universe.insert_named_pattern("blinker", 3, 3);
+    universe.tick();
+    let line = TickStats { generation: universe.generation(), population: universe.population() }.log_line(12.0);
+    for field in ["gen=1", "pop=3", "dt=12.0ms"] {
+        assert!(line.contains(field), "{:?} lacks {:?}", line, field);
+    }
+}
@@ -0,0 +1,114 @@
+//! Browser bindings, run with `wasm-pack test --node -- --features wasm`.
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use game_of_life::{init, Implementation, Universe};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn get_cells_u8_matches_get_cell() {
+    for implementation in [Implementation::Naive, Implementation::HashSet] {
+        let mut universe = Universe::new_with_seed(13, 7, implementation, 2);
+        universe.tick();
+        let cells = universe.get_cells_u8().to_vec();
+        assert_eq!(cells.len(), 13 * 7);
+        for row in 0..7 {
+            for col in 0..13 {
+                assert_eq!(cells[(row * 13 + col) as usize], universe.get_cell(row, col) as u8);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn ticks_with_the_browser_clock() {
+    let mut universe = Universe::new_default(16, 16);
+    universe.tick();
+    assert_eq!(universe.step_within(1.0) as u64 + 1, universe.generation());
+}
+
+#[wasm_bindgen_test]
+fn reset_rerandomizes_and_restarts_the_count() {
+    let mut universe = Universe::new_with_seed(32, 32, Implementation::Naive, 5);
+    universe.tick_js();
+    universe.tick_js();
+    let before = universe.get_cells_u8().to_vec();
+    universe.reset_js();
+    assert_ne!(universe.get_cells_u8().to_vec(), before);
+    assert_eq!(universe.generation(), 0);
+}
+
+#[wasm_bindgen_test]
+fn update_does_not_tick_while_paused() {
+    let mut universe = Universe::new_js(16, 16);
+    universe.set_running_js(false);
+    assert!(!universe.is_running_js());
+    assert_eq!(universe.update_js(1000.0), 0);
+    assert_eq!(universe.generation(), 0);
+}
+
+#[wasm_bindgen_test]
+fn rule_strings_round_trip_through_javascript() {
+    let mut universe = Universe::new_js(8, 8);
+    universe.set_rule_string_js("B36/S23").unwrap();
+    assert_eq!(universe.rule_string_js(), "B36/S23");
+    assert!(universe.set_rule_string_js("B9/S23").is_err());
+    assert_eq!(universe.rule_string_js(), "B36/S23");
+}
+
+#[wasm_bindgen_test]
+fn javascript_constructor_ticks_correctly() {
+    let mut universe = Universe::new_js(24, 18);
+    assert_eq!(universe.implementation(), &Implementation::Simd);
+    let mut reference = Universe::empty(24, 18, Implementation::Naive);
+    for (row, col) in universe.live_cells() {
+        reference.set_cell(row, col, true);
+    }
+    for _ in 0..10 {
+        universe.tick_js();
+        reference.tick();
+    }
+    assert_eq!(universe.live_cells(), reference.live_cells());
+}
+
+#[wasm_bindgen_test]
+fn set_size_keeps_the_cells_that_fit() {
+    let mut universe = Universe::new_js(10, 10);
+    universe.tick_js();
+    let kept: Vec<(u32, u32)> = universe.live_cells().into_iter().filter(|&(row, col)| row < 6 && col < 8).collect();
+    universe.set_size_js(8, 6);
+    assert_eq!((universe.width_js(), universe.height_js()), (8, 6));
+    assert_eq!(universe.live_cells(), kept);
+    assert_eq!(universe.get_cells_u8().length(), 8 * 6);
+    assert_eq!(universe.generation(), 1);
+}
+
+#[wasm_bindgen_test]
+fn hashset_is_not_transposed_on_non_square_grids() {
+    let mut naive = Universe::new_with_seed(13, 5, Implementation::Naive, 21);
+    let mut hashset = Universe::new_with_seed(13, 5, Implementation::HashSet, 21);
+    for _ in 0..10 {
+        naive.tick_js();
+        hashset.tick_js();
+        assert_eq!(hashset.get_cells_u8().to_vec(), naive.get_cells_u8().to_vec());
+    }
+}
+
+#[wasm_bindgen_test]
+fn tick_checked_settles_on_a_block() {
+    let mut universe = Universe::empty(8, 8, Implementation::Naive);
+    for (row, col) in [(3, 3), (3, 4), (4, 3)] {
+        universe.set_cell(row, col, true);
+    }
+    assert_eq!(universe.tick_checked_js(), 0);
+    assert_eq!(universe.tick_checked_js(), 1);
+}
+
+#[wasm_bindgen_test]
+fn init_can_be_called_repeatedly() {
+    init();
+    init();
+    let mut universe = Universe::new_js(8, 8);
+    init();
+    universe.tick_js();
+    assert_eq!(universe.generation(), 1);
+}